@@ -0,0 +1,29 @@
+//! `cargo bench` example: sort algorithms of crate `indxvec` against the Rust sort.
+//! Select the groups by name, e.g. `cargo bench u64` or `cargo bench -- --list`.
+use indxvec::Mutops;
+use times::{mutbenchf64, mutbenchu64, mutbenchu8};
+
+const NAMES: [&str; 3] = ["muthashsort", "rust-sort", "mutisort"];
+
+fn sort_u8() {
+    mutbenchu8(10..10000, 3000, 10, &NAMES, &[
+        |v: &mut [u8]| { v.muthashsort(|t: &u8| *t as f64); },
+        |v: &mut [u8]| { v.sort_unstable(); },
+        |v: &mut [u8]| { let n = v.len(); v.mutisort(0..n, |a, b| a.cmp(b)); } ]);
+}
+
+fn sort_u64() {
+    mutbenchu64(10..10000, 3000, 10, &NAMES, &[
+        |v: &mut [u64]| { v.muthashsort(|t: &u64| *t as f64); },
+        |v: &mut [u64]| { v.sort_unstable(); },
+        |v: &mut [u64]| { let n = v.len(); v.mutisort(0..n, |a, b| a.cmp(b)); } ]);
+}
+
+fn sort_f64() {
+    mutbenchf64(10..10000, 3000, 10, &NAMES, &[
+        |v: &mut [f64]| { v.muthashsort(|t: &f64| *t); },
+        |v: &mut [f64]| { v.sort_unstable_by(|a, b| a.total_cmp(b)); },
+        |v: &mut [f64]| { let n = v.len(); v.mutisort(0..n, |a, b| a.total_cmp(b)); } ]);
+}
+
+times::bench_main!(sort_u8, sort_u64, sort_f64);
@@ -0,0 +1,116 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Opt-in counting global allocator, delegating to the `System` allocator.
+/// Install it in the test or bench file with:
+/// `#[global_allocator] static ALLOC: times::allocs::CountingAlloc = times::allocs::CountingAlloc;`
+/// The bench functions then report the allocations count, the bytes allocated
+/// and the peak growth of the live heap bytes of each closure.
+/// Only the allocations made by the benchmarking thread are counted.
+pub struct CountingAlloc;
+
+/// Set by the first allocation (or reallocation) through `CountingAlloc`
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Number of allocations made by this thread
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+    /// Number of bytes allocated by this thread
+    static BYTES: Cell<usize> = const { Cell::new(0) };
+    /// Live heap bytes: allocated minus freed by this thread
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+    /// Highest `LIVE` since the last `resetpeak`
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+    /// `LIVE` at the last `resetpeak`
+    static BASE: Cell<isize> = const { Cell::new(0) };
+}
+
+/// Counts one allocation of `size` bytes by the current thread
+fn count(size: usize) {
+    // stored just once, not to contend for the flag's cache line on every allocation
+    if !INSTALLED.load(Ordering::Relaxed) {
+        INSTALLED.store(true, Ordering::Relaxed);
+    };
+    // try_with: the thread locals may be gone while the thread is exiting
+    ALLOCS.try_with(|a| a.set(a.get().wrapping_add(1))).ok();
+    BYTES.try_with(|b| b.set(b.get().wrapping_add(size))).ok();
+}
+
+/// Changes the live heap bytes of the current thread by `delta`, updating the peak
+fn live(delta: isize) {
+    LIVE.try_with(|l| {
+        let live = l.get().wrapping_add(delta);
+        l.set(live);
+        PEAK.try_with(|p| if live > p.get() { p.set(live) }).ok();
+    })
+    .ok();
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        live(layout.size() as isize);
+        unsafe { System.alloc(layout) }
+    }
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        live(layout.size() as isize);
+        unsafe { System.alloc_zeroed(layout) }
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        live(-(layout.size() as isize));
+        unsafe { System.dealloc(ptr, layout) }
+    }
+    /// Counted as a new allocation of `new_size` bytes
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size);
+        live(new_size as isize - layout.size() as isize);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// True when `CountingAlloc` is installed as the global allocator (and has been used)
+pub fn installed() -> bool {
+    INSTALLED.load(Ordering::Relaxed)
+}
+
+/// Allocations count and bytes allocated by the current thread so far
+/// (both zero when `CountingAlloc` is not installed)
+pub fn counts() -> (usize, usize) {
+    (ALLOCS.with(Cell::get), BYTES.with(Cell::get))
+}
+
+/// Starts a new peak of the live heap bytes of the current thread from its present level
+pub fn resetpeak() {
+    let live = LIVE.with(Cell::get);
+    BASE.with(|b| b.set(live));
+    PEAK.with(|p| p.set(live));
+}
+
+/// Peak growth of the live heap bytes of the current thread since the last `resetpeak`
+/// (zero when `CountingAlloc` is not installed)
+pub fn peak() -> usize {
+    (PEAK.with(Cell::get) - BASE.with(Cell::get)).max(0) as usize
+}
+
+/// Maximum resident set size of the process so far, in bytes, from `getrusage`.
+/// None on the platforms other than Linux.
+#[cfg(target_os = "linux")]
+pub fn maxrss() -> Option<usize> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+    // SAFETY: getrusage only writes into the provided rusage struct
+    let res = unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) };
+    // SAFETY: zero initialised and then filled in by getrusage
+    let usage = unsafe { usage.assume_init() };
+    (res == 0).then(|| 1024 * usage.ru_maxrss as usize) // Linux reports kilobytes
+}
+
+/// Maximum resident set size of the process so far, in bytes, from `getrusage`.
+/// None on the platforms other than Linux.
+#[cfg(not(target_os = "linux"))]
+pub fn maxrss() -> Option<usize> {
+    None
+}
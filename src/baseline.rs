@@ -0,0 +1,200 @@
+use crate::BenchReport;
+use indxvec::printing::*;
+use std::{fmt, fs, io, path::Path};
+
+/// Medians and mads of one algorithm for one data type and length, saved in a `Baseline`
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaselineEntry {
+    /// Type of the input data, as in `BenchReport::data`
+    pub data: String,
+    /// Length of the input data
+    pub length: usize,
+    /// Name of the algorithm
+    pub name: String,
+    /// Median of the execution times
+    pub median: f64,
+    /// Mad of the execution times
+    pub mad: f64,
+}
+
+/// Saved run (or runs), to be compared against by later runs.
+/// Its file format is the CSV output of `BenchReport`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Baseline {
+    /// All the saved entries
+    pub entries: Vec<BaselineEntry>,
+}
+
+/// Verdict of a comparison with the baseline
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Faster than the baseline by more than the uncertainty
+    Improved,
+    /// Slower than the baseline by more than the uncertainty
+    Regressed,
+    /// The difference is within the uncertainty
+    Unchanged,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Improved => write!(f, "{GR}improved{UN}"),
+            Verdict::Regressed => write!(f, "{RD}regressed{UN}"),
+            Verdict::Unchanged => write!(f, "{YL}unchanged{UN}"),
+        }
+    }
+}
+
+/// Change of one algorithm for one length versus the baseline
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    /// Length of the input data
+    pub length: usize,
+    /// Name of the algorithm
+    pub name: String,
+    /// Current median
+    pub median: f64,
+    /// Baseline median
+    pub basemedian: f64,
+    /// Relative change of the median in percent, negative is faster
+    pub change: f64,
+    /// Uncertainty of the difference: the sum of the current and the baseline mads
+    pub uncertainty: f64,
+    /// Improved, regressed or unchanged
+    pub verdict: Verdict,
+}
+
+/// Splits one CSV line into its (unquoted) fields
+fn csvsplit(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// InvalidData io error for malformed baseline files
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl From<&BenchReport> for Baseline {
+    fn from(report: &BenchReport) -> Self {
+        let mut entries = Vec::new();
+        for r in &report.results {
+            for a in &r.algos {
+                entries.push(BaselineEntry {
+                    data: report.data.clone(),
+                    length: r.length,
+                    name: a.name.clone(),
+                    median: a.median,
+                    mad: a.mad,
+                });
+            }
+        }
+        Baseline { entries }
+    }
+}
+
+impl Baseline {
+    /// Parses the CSV text written by `BenchReport::to_csv`
+    pub fn from_csv(csv: &str) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        for (no, line) in csv.lines().enumerate() {
+            if line.is_empty() || line == BenchReport::CSV_HEADER {
+                continue;
+            };
+            let fields = csvsplit(line);
+            if fields.len() < 5 {
+                return Err(invalid(format!("baseline line {}: too few fields", no + 1)));
+            };
+            let num = |i: usize| -> io::Result<f64> {
+                fields[i]
+                    .parse::<f64>()
+                    .map_err(|e| invalid(format!("baseline line {}: {e}", no + 1)))
+            };
+            entries.push(BaselineEntry {
+                data: fields[0].clone(),
+                length: fields[1]
+                    .parse::<usize>()
+                    .map_err(|e| invalid(format!("baseline line {}: {e}", no + 1)))?,
+                name: fields[2].clone(),
+                median: num(3)?,
+                mad: num(4)?,
+            });
+        }
+        Ok(Baseline { entries })
+    }
+
+    /// Loads a baseline file saved by `BenchReport::save_baseline`
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_csv(&fs::read_to_string(path)?)
+    }
+
+    /// Finds the saved entry for the given data type, length and algorithm name
+    pub fn find(&self, data: &str, length: usize, name: &str) -> Option<&BaselineEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.length == length && e.name == name && e.data == data)
+    }
+}
+
+impl BenchReport {
+    /// Saves this run as a baseline file (in the CSV format)
+    pub fn save_baseline(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_csv(path)
+    }
+
+    /// Compares this run against `baseline` and prints the change of each algorithm
+    /// for each length, with its verdict.
+    /// Algorithms and lengths missing from the baseline are skipped.
+    pub fn compare(&self, baseline: &Baseline) -> Vec<Comparison> {
+        outln!("\n{YL}Compared to baseline, data:{GR}{}{UN}", self.data);
+        let mut comparisons = Vec::new();
+        for r in &self.results {
+            outln!("\nLength: {BL}{}{UN}\n", r.length);
+            for a in &r.algos {
+                let Some(base) = baseline.find(&self.data, r.length, &a.name) else {
+                    continue;
+                };
+                let diff = a.median - base.median;
+                let uncertainty = a.mad + base.mad;
+                let verdict = if diff.abs() <= uncertainty {
+                    Verdict::Unchanged
+                } else if diff < 0.0 {
+                    Verdict::Improved
+                } else {
+                    Verdict::Regressed
+                };
+                let change = 100.0 * diff / base.median;
+                outln!(
+                    "{YL}{:<18}{GR}{:>13.0} {YL}was{GR}{:>13.0} {:>+8.2}% {verdict}",
+                    a.name, a.median, base.median, change
+                );
+                comparisons.push(Comparison {
+                    length: r.length,
+                    name: a.name.clone(),
+                    median: a.median,
+                    basemedian: base.median,
+                    change,
+                    uncertainty,
+                    verdict,
+                });
+            }
+        }
+        comparisons
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{rng::Rng, DataGen, Distribution};
+use core::cmp::Ordering;
+use std::{fmt, hash::{DefaultHasher, Hasher}};
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Distribution::Uniform => write!(f, "uniform"),
+            Distribution::Sorted => write!(f, "sorted"),
+            Distribution::Reversed => write!(f, "reversed"),
+            Distribution::NearlySorted(k) => write!(f, "nearly sorted ({k} swaps)"),
+            Distribution::Sawtooth(teeth) => write!(f, "sawtooth ({teeth} teeth)"),
+            Distribution::OrganPipe => write!(f, "organ pipe"),
+            Distribution::AllEqual => write!(f, "all equal"),
+            Distribution::FewDistinct(k) => write!(f, "few distinct ({k} values)"),
+            Distribution::Normal { mean, std } => write!(f, "normal ({mean},{std})"),
+            Distribution::Exponential { mean } => write!(f, "exponential ({mean})"),
+            Distribution::Zipf { n, s } => write!(f, "zipf ({n},{s})"),
+        }
+    }
+}
+
+/// Standard normal random number, by the Box-Muller transform
+fn rannormal(rng: &mut Rng) -> f64 {
+    let u1 = 1.0 - rng.f64(); // in (0,1]
+    let u2 = rng.f64();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+/// Generates `len` items of the distribution `dist` from `rng`.
+/// `ranv(rng,len)` gives the uniformly distributed items,
+/// `fromf64` converts the samples of the continuous distributions.
+fn distributed<T: Copy + PartialOrd>(
+    len: usize,
+    dist: &Distribution,
+    rng: &mut Rng,
+    ranv: fn(&mut Rng, usize) -> Vec<T>,
+    fromf64: fn(f64) -> T,
+) -> Vec<T> {
+    let ascending = |a: &T, b: &T| a.partial_cmp(b).unwrap_or(Ordering::Equal);
+    match *dist {
+        Distribution::Uniform => ranv(rng, len),
+        Distribution::Sorted => {
+            let mut v = ranv(rng, len);
+            v.sort_unstable_by(ascending);
+            v
+        }
+        Distribution::Reversed => {
+            let mut v = ranv(rng, len);
+            v.sort_unstable_by(|a, b| ascending(b, a));
+            v
+        }
+        Distribution::NearlySorted(k) => {
+            let mut v = ranv(rng, len);
+            v.sort_unstable_by(ascending);
+            for _ in 0..if len > 0 { k } else { 0 } {
+                v.swap(rng.index(len), rng.index(len));
+            }
+            v
+        }
+        Distribution::Sawtooth(teeth) => {
+            let mut v = ranv(rng, len);
+            for tooth in v.chunks_mut(len.div_ceil(teeth.max(1)).max(1)) {
+                tooth.sort_unstable_by(ascending);
+            }
+            v
+        }
+        Distribution::OrganPipe => {
+            let mut sorted = ranv(rng, len);
+            sorted.sort_unstable_by(ascending);
+            // even positions going up, then odd positions coming down
+            sorted.iter().step_by(2).chain(sorted.iter().skip(1).step_by(2).rev()).copied().collect()
+        }
+        Distribution::AllEqual => vec![ranv(rng, 1)[0]; len],
+        Distribution::FewDistinct(k) => {
+            let values = ranv(rng, k.max(1));
+            (0..len).map(|_| values[rng.index(values.len())]).collect()
+        }
+        Distribution::Normal { mean, std } => {
+            (0..len).map(|_| fromf64(mean + std * rannormal(rng))).collect()
+        }
+        Distribution::Exponential { mean } => {
+            (0..len).map(|_| fromf64(-mean * (1.0 - rng.f64()).ln())).collect()
+        }
+        Distribution::Zipf { n, s } => {
+            // cumulative weights of ranks 1..=n, inverted by binary search
+            let mut cumulative = Vec::with_capacity(n.max(1));
+            let mut sum = 0_f64;
+            for k in 1..=n.max(1) {
+                sum += (k as f64).powf(-s);
+                cumulative.push(sum);
+            }
+            (0..len)
+                .map(|_| {
+                    let u = sum * rng.f64();
+                    let rank = cumulative.partition_point(|&c| c <= u) + 1;
+                    fromf64(rank.min(n.max(1)) as f64)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Implements `DataGen` for end types from their uniform generators of single items.
+/// Continuous samples are converted to integers by rounding and saturating.
+macro_rules! scalar_datagen {
+    ($($t:ty: $ran:expr, $fromf64:expr;)*) => {$(
+        impl DataGen for $t {
+            fn label() -> String {
+                stringify!($t).to_string()
+            }
+            fn generate(_rows: usize, len: usize, dist: &Distribution, rng: &mut Rng) -> Vec<Self> {
+                distributed(len, dist, rng, |r, d| (0..d).map(|_| $ran(r)).collect(), $fromf64)
+            }
+            fn fingerprint(items: &[Self]) -> u64 {
+                let mut hasher = DefaultHasher::new();
+                for x in items { hasher.write(&x.to_le_bytes()); }
+                hasher.finish()
+            }
+        }
+    )*};
+}
+
+scalar_datagen! {
+    u8: Rng::u8, |x| x.round() as u8;
+    u16: Rng::u16, |x| x.round() as u16;
+    u32: |r: &mut Rng| r.ubits(32) as u32, |x| x.round() as u32;
+    u64: Rng::u64, |x| x.round() as u64;
+    u128: |r: &mut Rng| (r.u64() as u128) << 64 | r.u64() as u128, |x| x.round() as u128;
+    usize: |r: &mut Rng| r.u64() as usize, |x| x.round() as usize;
+    i8: |r: &mut Rng| r.u8() as i8, |x| x.round() as i8;
+    i16: |r: &mut Rng| r.u16() as i16, |x| x.round() as i16;
+    i32: |r: &mut Rng| r.ubits(32) as i32, |x| x.round() as i32;
+    i64: Rng::i64, |x| x.round() as i64;
+    f32: |r: &mut Rng| r.ubits(24) as f32 / (1_u32 << 24) as f32, |x| x as f32;
+    f64: Rng::f64, |x| x;
+}
+
+/// Rows of `&[Vec<T>]` inputs: `rows` vectors of `len` items each
+impl<T: DataGen> DataGen for Vec<T> {
+    fn label() -> String {
+        format!("Vec<{}>", T::label())
+    }
+    fn generate(rows: usize, len: usize, dist: &Distribution, rng: &mut Rng) -> Vec<Self> {
+        (0..rows).map(|_| T::generate(1, len, dist, rng)).collect()
+    }
+    fn bytes(rows: usize, len: usize) -> usize {
+        rows * (std::mem::size_of::<Self>() + T::bytes(1, len))
+    }
+    fn fingerprint(items: &[Self]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for row in items { hasher.write_u64(T::fingerprint(row)); }
+        hasher.finish()
+    }
+}
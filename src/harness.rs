@@ -0,0 +1,132 @@
+use indxvec::printing::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Suppresses all the printouts of the bench functions
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// True when the printouts of the bench functions are suppressed
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Suppresses (or restores) the printouts of the bench functions and of the reports.
+/// Their `BenchReport`s are still returned.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Set by `--quiet`, suppresses the headings of the bench functions, but not their results
+static TERSE: AtomicBool = AtomicBool::new(false);
+
+/// True when the headings of the bench functions are suppressed
+pub fn terse() -> bool {
+    TERSE.load(Ordering::Relaxed)
+}
+
+/// Suppresses (or restores) the headings of the bench functions,
+/// leaving just the lengths and the result rows of the reports
+pub fn set_terse(terse: bool) {
+    TERSE.store(terse, Ordering::Relaxed);
+}
+
+/// Libtest style command line arguments of a `cargo bench` harness
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    /// Run only the groups whose names contain one of these (all when empty)
+    pub filters: Vec<String>,
+    /// Do not run the groups whose names contain one of these (`--skip`)
+    pub skip: Vec<String>,
+    /// Just list the selected groups (`--list`)
+    pub list: bool,
+    /// Names must equal the filters, rather than contain them (`--exact`)
+    pub exact: bool,
+    /// Terse printouts, just the results, without the group banners and the headings (`--quiet` or `-q`)
+    pub quiet: bool,
+    /// Run as benchmarks (`--bench`, passed by `cargo bench`), rather than as tests
+    pub bench: bool,
+}
+
+/// Libtest options taking a value, which are accepted and ignored
+const VALUED: [&str; 5] = ["--color", "--format", "--logfile", "--test-threads", "-Z"];
+
+impl Args {
+    /// Parses the arguments (without the program name).
+    /// Other libtest options are ignored.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--list" => parsed.list = true,
+                "--exact" => parsed.exact = true,
+                "--quiet" | "-q" => parsed.quiet = true,
+                "--bench" => parsed.bench = true,
+                "--skip" => parsed.skip.extend(args.next()),
+                a if VALUED.contains(&a) => {
+                    args.next();
+                }
+                a if a.starts_with('-') => (),
+                _ => parsed.filters.push(arg),
+            }
+        }
+        parsed
+    }
+    /// Parses the arguments of this process
+    pub fn from_env() -> Self {
+        Self::parse(std::env::args().skip(1))
+    }
+    /// Whether the group named `name` is selected by the filters
+    pub fn selected(&self, name: &str) -> bool {
+        let matches = |f: &String| if self.exact { name == f } else { name.contains(f.as_str()) };
+        (self.filters.is_empty() || self.filters.iter().any(matches)) && !self.skip.iter().any(matches)
+    }
+}
+
+/// Runs (or lists) the named benchmark `groups` selected by `args`,
+/// returns the number of the groups run (or listed).
+/// Without `--bench`, e.g. under `cargo test --benches`, the groups are skipped, as in libtest.
+pub fn run(args: &Args, groups: &[(&str, fn())]) -> usize {
+    let selected: Vec<_> = groups.iter().filter(|(name, _)| args.selected(name)).collect();
+    if args.list {
+        for (name, _) in &selected {
+            println!("{name}: bench");
+        }
+        return selected.len();
+    };
+    if !args.bench {
+        println!("\n{YL}Benchmark groups skipped: {GR}{}{YL} (not run by cargo bench){UN}", selected.len());
+        return 0;
+    };
+    set_terse(args.quiet);
+    for (name, group) in &selected {
+        if !args.quiet {
+            println!("\n{YL}Benchmark group: {GR}{name}{UN}");
+        };
+        group();
+    }
+    println!(
+        "\n{YL}Benchmark groups run: {GR}{}{YL} filtered out: {GR}{}{UN}",
+        selected.len(),
+        groups.len() - selected.len()
+    );
+    selected.len()
+}
+
+/// Entry point of a `cargo bench` target with `harness = false`:
+/// generates `main`, running the listed benchmark groups (functions of no arguments)
+/// selected by the command line, e.g. `cargo bench sort -- --exact`.
+/// ```ignore
+/// fn sorts() { times::mutbenchu64(10..1000, 100, 10, &NAMES, &CLOSURES); }
+/// times::bench_main!(sorts);
+/// ```
+#[macro_export]
+macro_rules! bench_main {
+    ($($group:path),+ $(,)?) => {
+        fn main() {
+            $crate::harness::run(
+                &$crate::harness::Args::from_env(),
+                &[$((stringify!($group), $group as fn())),+],
+            );
+        }
+    };
+}
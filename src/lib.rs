@@ -1,491 +1,1059 @@
 #![warn(missing_docs)]
 //! Benchmark for timing algorithms
 
-use std::time::Instant;
+use std::{
+    collections::HashMap, fmt, hint::black_box,
+    sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError},
+    time::{Duration, Instant} };
 use core::ops::Range;
-use indxvec::{printing::*, Indices, Vecops};
+use indxvec::{printing::*, Vecops};
 use medians::Medianf64;
-use ran::*;
-
-fn report(names: &[&str], meds: &[f64], stderrs: &[f64]) {
-    let medsx = meds.isort_indexed(0..meds.len(),|a:&f64,b| a.total_cmp(b));
-    let meds_sorted = medsx.unindex(meds, true);
-    let names_sorted = medsx.unindex(names, true);
-    let stderrs_sorted = medsx.unindex(stderrs, true);
-    for i in 0..names.len() {
-        println!(
-            "{YL}{:<18}{GR}{:>13.0} ±{:>7.0} ~{:>5.2}%{YL} {:>7.4}{UN}",
-            names_sorted[i],
-            meds_sorted[i],
-            stderrs_sorted[i],
-            100.0*stderrs_sorted[i]/meds_sorted[i],
-            meds_sorted[i]/meds_sorted[0]);
-    };
+use ran::{get_seed, set_seeds};
+use rng::Rng;
+use timer::Clock;
+
+/// `print!` unless the printouts are suppressed by `harness::set_quiet`
+macro_rules! out {
+    ($($arg:tt)*) => { if !$crate::harness::quiet() { print!($($arg)*); } };
 }
-fn heading(data:&str,c1:usize,c2:usize,step:usize,rows:usize,repeats:usize) {
-    println!(
-        "\n{YL}Data:{GR}{data} {YL}lengths:{GR}{c1}-{c2} {YL}step:{GR}{step} {YL}rows:{GR}{rows} {YL}repeats:{GR}{repeats}{UN}"
-    );
+
+/// `println!` unless the printouts are suppressed by `harness::set_quiet`
+macro_rules! outln {
+    ($($arg:tt)*) => { if !$crate::harness::quiet() { println!($($arg)*); } };
 }
 
-/// Tests of listed `closures` that take no or constant arguments, named in `names`
-/// `repeats` runs of each closure.
-pub fn bench(repeats: usize, names: &[&str], closures: &[fn()]) {
-    let algno = names.len(); 
-    println!(
-        "\n{YL}Input Data: {GR}none {YL}repeats: {GR}{repeats}{UN}"
-    );
-    let mut meds = Vec::with_capacity(algno);
-    let mut stderrs = Vec::with_capacity(algno);
-    let seed = get_seed(); // store the seed, whatever it is
-    for closure in closures {
-        // reintialise random numbers generator to the same seed for each closure
-        set_seeds(seed);
-        let mut times: Vec<f64> = Vec::with_capacity(repeats);
-        for _ in 0..repeats {
-            let now = Instant::now(); // = UNIX_EPOCH.elapsed().unwrap().as_nanos() as u64;timer.start();
-            closure();
-            times.push(now.elapsed().as_nanos() as f64);
-        };
-        let med = times.medf_checked().expect("bench Nan detected");
-        meds.push(med);
-        stderrs.push(times.madf(med));
+/// `print!` of the headings, unless suppressed by `harness::set_quiet` or `harness::set_terse`
+macro_rules! head {
+    ($($arg:tt)*) => { if !$crate::harness::terse() { out!($($arg)*); } };
+}
+
+/// `println!` of the headings, unless suppressed by `harness::set_quiet` or `harness::set_terse`
+macro_rules! headln {
+    ($($arg:tt)*) => { if !$crate::harness::terse() { outln!($($arg)*); } };
+}
+
+/// Implementations of trait DataGen for the supported end types and `Vec<T>`
+pub mod datagen;
+/// Machine readable output of `BenchReport`s
+pub mod output;
+/// Saved baselines and regression comparisons between runs
+pub mod baseline;
+/// Statistical tests
+pub mod stats;
+/// Calibration of the timer overhead and resolution
+pub mod timer;
+/// Memory usage: opt-in counting global allocator and max resident set size
+pub mod allocs;
+/// Owned random numbers generator of the bench engine
+pub mod rng;
+/// `cargo bench` harness: the `bench_main!` entry point and its command line arguments
+pub mod harness;
+
+/// Random data generation for the generic bench functions `benchgen` and `mutbenchgen`.  
+/// Implemented for the end types `u8,u16,u32,u64,u128,usize,i8,i16,i32,i64,f32,f64`
+/// and for `Vec<T>` of them,
+/// so that new element types and input shapes just need another implementation.
+pub trait DataGen: Sized {
+    /// Name of the type for the printouts, e.g. `u8` or `Vec<u8>`
+    fn label() -> String;
+    /// New random vector of `len` items from `rng`, distributed according to `dist`.
+    /// `Vec<T>` implementation generates `rows` Vecs of `len` items each instead.
+    fn generate(rows: usize, len: usize, dist: &Distribution, rng: &mut Rng) -> Vec<Self>;
+    /// Approximate size in bytes of one input generated by `generate(rows,len,..)`,
+    /// for the memory budget of the batches
+    fn bytes(_rows: usize, len: usize) -> usize {
+        len * std::mem::size_of::<Self>()
     }
-    report(names, &meds, &stderrs);
+    /// Hash of the `items` (bitwise for the floats), by which `Config::verify` compares the outputs
+    fn fingerprint(items: &[Self]) -> u64;
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn mutbenchu8(
-    lengths: Range<usize>,
-    step: usize,
-    repeats: usize,
-    names: &[&str],
-    closures: &[fn(&mut [u8])],
-) {
-    let algno = names.len(); 
-    heading("&mut[u8]",lengths.start,lengths.end,step,1,repeats); 
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let mut data = ranv_u8(d).expect("ranv_u8 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&mut data);
-                times.push(now.elapsed().as_nanos() as f64);
-            };
-            let med = times.medf_checked().expect("mutbenchu8 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med));
-        }
-        report(names, &meds, &stderrs);
+/// Source of the measurements taken by all the bench loops around each timed run,
+/// selected by `Config::measurement`.
+/// Implemented for the `timer::Clock`s. Other implementations can plug in e.g. cycle counters,
+/// context switches or page faults from `getrusage`, or the application's own counters.
+pub trait Measurement: Send + Sync {
+    /// Name for the printouts, e.g. `wall`
+    fn name(&self) -> String;
+    /// Unit of the values for the printouts, e.g. `ns`
+    fn unit(&self) -> String;
+    /// Reading taken at the start of a run
+    fn start(&self) -> f64;
+    /// Reading taken at the end of a run, by default the same as `start`
+    fn stop(&self) -> f64 {
+        self.start()
+    }
+    /// Measured value of a run from its `start` and `stop` readings, by default their difference
+    fn value(&self, start: f64, stop: f64) -> f64 {
+        stop - start
+    }
+    /// The clock read by this measurement, if any. Only the wall clock is calibrated
+    /// (see `timer::calibration`)
+    fn clock(&self) -> Option<timer::Clock> {
+        None
     }
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn mutbenchu16(
-    lengths: Range<usize>,
-    step: usize,
-    repeats: usize,
-    names: &[&str],
-    closures: &[fn(&mut [u16])],
-) {
-    let algno = names.len();
-    heading("&mut[u16]",lengths.start,lengths.end,step,1,repeats); 
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            for _ in 0..repeats {
-                let mut data = ranv_u16(d).expect("ranv_u16 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&mut data);
-                times.push(now.elapsed().as_nanos() as f64);
-            };
-            let med = times.medf_checked().expect("mutbenchu16 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med));
+impl fmt::Debug for dyn Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.name(), self.unit())
+    }
+}
+
+/// Distribution of the generated input data, selected by `Config::dist`.
+/// The ordered shapes are made from uniformly distributed items.
+/// Samples of the continuous distributions (normal, exponential, zipf)
+/// are rounded (and saturated) for the integer end types.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Distribution {
+    /// Full range of the integer types, [0,1) for floats
+    #[default]
+    Uniform,
+    /// Ascending order
+    Sorted,
+    /// Descending order
+    Reversed,
+    /// Ascending order with the given number of random swaps
+    NearlySorted(usize),
+    /// The given number of ascending runs (teeth)
+    Sawtooth(usize),
+    /// Ascending, then descending
+    OrganPipe,
+    /// All items equal to one random value
+    AllEqual,
+    /// Items drawn from the given number of random values
+    FewDistinct(usize),
+    /// Normal (gaussian) distribution
+    Normal {
+        /// Mean
+        mean: f64,
+        /// Standard deviation
+        std: f64,
+    },
+    /// Exponential distribution
+    Exponential {
+        /// Mean
+        mean: f64,
+    },
+    /// Zipf distribution of ranks 1..=n, with the probability of rank k proportional to 1/k^s
+    Zipf {
+        /// Number of ranks
+        n: usize,
+        /// Exponent
+        s: f64,
+    },
+}
+
+/// Options of the generic bench functions.
+/// `Config::default()` gives the plain behaviour of the typed bench functions:
+/// no warm-up and a fixed number of repeats.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Distribution of the generated input data
+    pub dist: Distribution,
+    /// Untimed warm-up runs of each closure before its timed runs, for each length
+    pub warmup: usize,
+    /// Auto repeats mode: after the given number of `repeats`, keep repeating
+    /// until the mad% of the median drops below this target
+    pub target: Option<f64>,
+    /// Auto repeats mode: time budget for the timed runs of one closure for one length
+    pub budget: Duration,
+    /// Auto repeats mode: maximum number of repeats
+    pub maxrepeats: usize,
+    /// Mutable benches: check that the mutated outputs of all the closures equal
+    /// those of the first closure on identical input (of the first one to run on it,
+    /// in the interleaved mode), panic when they do not.
+    /// The outputs are compared by their `DataGen::fingerprint`s, which cost 8 bytes
+    /// per call of the length being run, kept until the next length
+    pub verify: bool,
+    /// Subtract the overhead of the measurement itself, timed by measuring an empty closure, from each wall clock time
+    pub subtract: bool,
+    /// Batched timing for very fast closures: each timing sample runs a batch of calls
+    /// on pre-generated inputs, the batch size being doubled until the sample
+    /// takes at least this long. The reported times are per call.
+    pub batch: Option<Duration>,
+    /// Measurement for all the statistics: a `timer::Clock` (wall clock or CPU time),
+    /// or any other `Measurement`
+    pub measurement: Arc<dyn Measurement>,
+    /// Another measurement, whose medians are reported alongside those of `measurement`,
+    /// e.g. the thread CPU time next to the wall time
+    pub secondary: Option<Arc<dyn Measurement>>,
+    /// Thread counts swept by the scaling benches, by default `threadcounts()`.
+    /// One thread is always included, as the reference of the speedups
+    pub threads: Option<Vec<usize>>,
+    /// Interleaved mode: each repeat generates one input and runs every closure
+    /// on its own copy of it, in a random order, so that any machine drift
+    /// affects all the closures alike
+    pub interleave: bool,
+    /// Name of the reference closure for the paired speedups, by default the fastest.
+    /// The bench functions panic when none of their closures has this name
+    pub reference: Option<String>,
+    /// Seed of the generated input data, for reproducing a run,
+    /// 0 for a new random seed each run (see `rng::Rng`)
+    pub seed: u64,
+    /// All the bench functions of the process run one at a time, holding one lock,
+    /// so the closures must not call bench functions themselves.
+    /// Warn when this bench had to wait for another one to finish
+    pub warnwait: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            dist: Distribution::Uniform,
+            warmup: 0,
+            target: None,
+            budget: Duration::from_secs(5),
+            maxrepeats: 10000,
+            verify: false,
+            subtract: false,
+            batch: None,
+            measurement: Arc::new(Clock::Wall),
+            secondary: None,
+            threads: None,
+            interleave: false,
+            reference: None,
+            seed: 0,
+            warnwait: false,
         }
-        report(names, &meds, &stderrs);
     }
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`.  
-/// Lengths range is iterated over by step.  
-/// `repeats` runs of each closure for each length of data
-pub fn mutbenchu64(
-    lengths: Range<usize>,
-    step: usize,
-    repeats: usize,
-    names: &[&str],
-    closures: &[fn(&mut [u64])],
-) {
-    let algno = names.len();
-    heading("&mut[u64]",lengths.start,lengths.end,step,1,repeats); 
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let mut data = ranv_u64(d).expect("ranv_u64 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&mut data);
-                times.push(now.elapsed().as_nanos() as f64);
+/// Execution times statistics of one named closure
+#[derive(Clone, Debug)]
+pub struct AlgoStats {
+    /// Name of the closure, as given in `names`
+    pub name: String,
+    /// Median of the execution times per call, in the unit of `Config::measurement`
+    /// (nanoseconds for the clocks)
+    pub median: f64,
+    /// Mad (median of absolute differences from the median) of the execution times, in the same unit
+    pub mad: f64,
+    /// Number of timed runs
+    pub samples: usize,
+    /// Number of calls in each timed run (1 unless batched)
+    pub batch: usize,
+    /// Raw execution times per call in the unit of `Config::measurement`,
+    /// in the order in which they were taken
+    pub times: Vec<f64>,
+    /// Median value per call of the `Config::secondary` measurement (0 when not set)
+    pub second: f64,
+    /// Number of threads given to the closure by the scaling benches, 1 otherwise
+    pub threads: usize,
+    /// Speedup of the median relative to the same closure with one thread, 1 when not scaling
+    pub speedup: f64,
+    /// Median of the paired speedups versus the reference closure (see `Config::reference`),
+    /// repeat by repeat on the same data
+    pub paired: f64,
+    /// Mad of the paired speedups
+    pub pairedmad: f64,
+    /// Median number of heap allocations per call (0 unless `allocs::CountingAlloc` is installed)
+    pub allocs: f64,
+    /// Median number of bytes allocated per call (0 unless `allocs::CountingAlloc` is installed)
+    pub bytes: f64,
+    /// Median peak growth of the live heap bytes during a call, or during a batch of calls
+    /// (0 unless `allocs::CountingAlloc` is installed)
+    pub peak: f64,
+    /// Growth of the process max resident set size in bytes over all the runs of this closure
+    /// (Linux only, 0 elsewhere). The max resident set size is a high-water mark of the whole process:
+    /// only the first closure to exceed it shows the growth, the others show 0 for the same memory,
+    /// so this depends on the order of the runs and is no per closure measurement
+    pub maxrss: f64,
+    /// P-value of the Mann-Whitney test against the fastest closure (1 for the fastest itself)
+    pub pvalue: f64,
+    /// Rank group, starting from 1 for the fastest. Closures in the same group
+    /// are not significantly different from the fastest closure of their group
+    pub group: usize,
+}
+
+impl AlgoStats {
+    /// Computes the statistics of the raw per call `samples`, panics with `msg` on NaN.
+    /// `maxrss` is the growth of the max resident set size over all the runs.
+    fn new(name: &str, samples: Vec<Sample>, batch: usize, maxrss: f64, msg: &str) -> Self {
+        let times: Vec<f64> = samples.iter().map(|s| s.time).collect();
+        let median = times.medf_checked().expect(msg);
+        let mad = times.madf(median);
+        let second = samples.iter().map(|s| s.second).collect::<Vec<f64>>().medf_checked().expect(msg);
+        let allocs = samples.iter().map(|s| s.allocs).collect::<Vec<f64>>().medf_checked().expect(msg);
+        let bytes = samples.iter().map(|s| s.bytes).collect::<Vec<f64>>().medf_checked().expect(msg);
+        let peak = samples.iter().map(|s| s.peak).collect::<Vec<f64>>().medf_checked().expect(msg);
+        AlgoStats {
+            name: name.to_string(), median, mad, samples: times.len(), batch, times, second,
+            threads: 1, speedup: 1.0, paired: 1.0, pairedmad: 0.0, allocs, bytes, peak, maxrss, pvalue: 1.0, group: 1 }
+    }
+    /// Mad as a percentage of the median
+    pub fn madpc(&self) -> f64 {
+        100.0 * self.mad / self.median
+    }
+    /// Parallel efficiency: the speedup per thread, 1 for perfect scaling
+    pub fn efficiency(&self) -> f64 {
+        self.speedup / self.threads as f64
+    }
+}
+
+/// Statistics of all the closures for one length of the input data
+#[derive(Clone, Debug)]
+pub struct LengthReport {
+    /// Length of the input data vectors (0 when there is no input data)
+    pub length: usize,
+    /// Seed of the `rng::Rng` of the input data for this length
+    pub seed: u64,
+    /// Statistics of each closure, in the same order as their `names`
+    pub algos: Vec<AlgoStats>,
+}
+
+impl LengthReport {
+    /// Collects the statistics of the closures for one length,
+    /// testing their significance against the fastest and grouping the ties,
+    /// and pairing their times with those of the closure named `reference` (or of the fastest),
+    /// with one thread when scaling
+    fn new(length: usize, seed: u64, algos: Vec<AlgoStats>, reference: Option<&str>) -> Self {
+        let mut lr = LengthReport { length, seed, algos };
+        let ranking = lr.ranking();
+        let Some(&winner) = ranking.first() else { return lr; };
+        let base = match reference {
+            Some(name) => lr.algos.iter()
+                .position(|a| a.name == name || (a.threads == 1 && a.name == format!("{name}/1")))
+                .unwrap_or_else(|| panic!("{}", noreference(name))),
+            None => winner,
+        };
+        for i in 0..lr.algos.len() {
+            let (paired, pairedmad) = stats::pairedspeedup(&lr.algos[base].times, &lr.algos[i].times);
+            lr.algos[i].paired = paired;
+            lr.algos[i].pairedmad = pairedmad;
+        }
+        let mut leader = winner;
+        let mut group = 1;
+        for &i in &ranking {
+            let pvalue = stats::mannwhitney(&lr.algos[i].times, &lr.algos[winner].times);
+            if stats::mannwhitney(&lr.algos[i].times, &lr.algos[leader].times) < stats::ALPHA {
+                group += 1;
+                leader = i;
             };
-            let med = times.medf_checked().expect("mutbenchu64 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med));
+            lr.algos[i].pvalue = if i == winner { 1.0 } else { pvalue };
+            lr.algos[i].group = group;
         }
-        report(names, &meds, &stderrs);
+        lr
+    }
+    /// The fastest closure (with the lowest median)
+    pub fn fastest(&self) -> &AlgoStats {
+        self.algos
+            .iter()
+            .min_by(|a, b| a.median.total_cmp(&b.median))
+            .expect("LengthReport with no closures")
+    }
+    /// Indices of `algos`, sorted from the fastest to the slowest
+    pub fn ranking(&self) -> Vec<usize> {
+        let meds: Vec<f64> = self.algos.iter().map(|a| a.median).collect();
+        meds.isort_indexed(0..meds.len(), |a: &f64, b| a.total_cmp(b))
     }
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn mutbenchf64( 
-    lengths: Range<usize>,
-    step: usize,
-    repeats: usize,
-    names: &[&str],
-    closures: &[fn(&mut [f64])],
-) {
-    let algno = names.len();
-    heading("&mut[f64]",lengths.start,lengths.end,step,1,repeats); 
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let mut data = ranv_f64(d).expect("ranv_f64 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&mut data);
-                times.push(now.elapsed().as_nanos() as f64);
+/// All the results of one benchmark run, as returned by the bench functions
+#[derive(Clone, Debug)]
+pub struct BenchReport {
+    /// Type of the input data, e.g. `&mut[u8]`, or `none`
+    pub data: String,
+    /// Range of the data lengths
+    pub lengths: Range<usize>,
+    /// Step by which the `lengths` range was iterated over
+    pub step: usize,
+    /// Number of rows (Vecs) in each `&[Vec<T>]` input, 1 for `&[T]`
+    pub rows: usize,
+    /// Requested number of repeated runs of each closure for each length
+    pub repeats: usize,
+    /// Seed of the whole run: `Config::seed`, or the new random seed when that is 0
+    /// (for `benchfnmut`, the `ran` seed given to the closures)
+    pub seed: u64,
+    /// Results for each length, in increasing order of lengths
+    pub results: Vec<LengthReport>,
+}
+
+fn report(cfg: &Config, lr: &LengthReport) {
+    if lr.algos.is_empty() { return; };
+    let fastest = lr.fastest().median;
+    let unit = cfg.measurement.unit();
+    let clocks = (cfg.measurement.clock(), cfg.secondary.as_ref().and_then(|m| m.clock()));
+    let calibrated = clocks.0 == Some(Clock::Wall);
+    let scaling = lr.algos.iter().any(|a| a.threads > 1);
+    for i in lr.ranking() {
+        let a = &lr.algos[i];
+        out!(
+            "{YL}{:<18}{GR}{:>13.0}{unit} ±{:>7.0} ~{:>5.2}%{YL} {:>7.4} {CY}{:>3}{GR} p:{:>6.4}",
+            a.name,
+            a.median,
+            a.mad,
+            a.madpc(),
+            a.median/fastest,
+            a.group,
+            a.pvalue);
+        if lr.algos.len() > 1 { out!(" {YL}paired:{GR}{:>6.4} ±{:>6.4}", a.paired, a.pairedmad); };
+        if scaling {
+            out!(" {YL}speedup:{GR}{:>6.3} {YL}efficiency:{GR}{:>5.1}%", a.speedup, 100.0*a.efficiency());
+        };
+        if cfg.target.is_some() { out!(" {YL}repeats:{GR}{}", a.samples); };
+        if cfg.batch.is_some() { out!(" {YL}batch:{GR}{}", a.batch); };
+        if let Some(m) = &cfg.secondary {
+            out!(" {YL}{}:{GR}{:.0}{}", m.name(), a.second, m.unit());
+            let (wall, cpu) = match clocks {
+                (Some(Clock::Wall), Some(Clock::ThreadCpu)) => (a.median, a.second),
+                (Some(Clock::ThreadCpu), Some(Clock::Wall)) => (a.second, a.median),
+                _ => (0.0, 0.0),
             };
-            let med = times.medf_checked().expect("mutbenchf64 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med));
-        }
-        report(names, &meds, &stderrs);
+            if wall > timer::DESCHEDULED*cpu { out!(" {RD}descheduled!"); };
+        };
+        if allocs::installed() {
+            out!(" {YL}allocs:{GR}{:.0} {YL}bytes:{GR}{:.0} {YL}peak:{GR}{:.0} {YL}maxrss:{GR}+{:.0}",
+                a.allocs, a.bytes, a.peak, a.maxrss);
+        };
+        // each sample times a whole batch of calls
+        let sample = a.median * a.batch as f64;
+        if calibrated && sample < timer::RESOLUTION_MULTIPLE*timer::calibration().resolution {
+            out!(" {RD}near timer resolution!");
+        };
+        outln!("{UN}");
+    };
+}
+
+/// Measurements of one timed run
+#[derive(Clone, Copy, Debug, Default)]
+struct Sample {
+    /// Value of the measurement, e.g. time in nanoseconds
+    time: f64,
+    /// Value of the secondary measurement
+    second: f64,
+    /// Wall time in nanoseconds, for the batch size calibration
+    wall: f64,
+    /// Heap allocations count
+    allocs: f64,
+    /// Bytes allocated
+    bytes: f64,
+    /// Peak growth of the live heap bytes
+    peak: f64,
+}
+
+/// Runs `calls` (a batch of calls of one closure), taking the measurements of `cfg`
+/// and counting their allocations
+fn measure(cfg: &Config, calls: impl FnOnce()) -> Sample {
+    let (allocs, bytes) = allocs::counts();
+    allocs::resetpeak();
+    let second = cfg.secondary.as_ref().map(|m| (m, m.start()));
+    let now = Instant::now();
+    let start = cfg.measurement.start();
+    calls();
+    let stop = cfg.measurement.stop();
+    let wall = now.elapsed().as_nanos() as f64;
+    let time = cfg.measurement.value(start, stop);
+    let second = second.map_or(0.0, |(m, start)| m.value(start, m.stop()));
+    let (allocs2, bytes2) = allocs::counts();
+    Sample {
+        time,
+        second,
+        wall,
+        allocs: allocs2.wrapping_sub(allocs) as f64,
+        bytes: bytes2.wrapping_sub(bytes) as f64,
+        peak: allocs::peak() as f64,
     }
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn benchu8(
-    lengths: Range<usize>,
-    step: usize,
+/// Largest batch of calls in one timed run
+const MAXBATCH: usize = 1 << 20;
+
+/// Memory budget in bytes of the pre-generated inputs of one batch
+const MAXBYTES: usize = 1 << 26;
+
+/// Most distinct inputs of a batch of read only calls, which cycle through them
+const POOL: usize = 16;
+
+/// Batch size of one closure, 1 unless `cfg.batch` is set:
+/// doubled until a batch of calls takes at least `cfg.batch` time, or reaches `max` calls.
+/// `timeit(n)` runs a batch of n calls and returns their total measurements.
+fn batchsize(cfg: &Config, max: usize, mut timeit: impl FnMut(usize) -> Sample) -> usize {
+    let Some(min) = cfg.batch else { return 1; };
+    let min = min.as_nanos() as f64;
+    let mut n = 1;
+    while 2*n <= max && timeit(n).wall < min { n *= 2; };
+    n
+}
+
+/// Overhead of `measure` with the measurements of `cfg`: the median time of measuring nothing.
+/// Zero unless `cfg.subtract` is set and the wall clock is measured.
+fn overhead(cfg: &Config) -> f64 {
+    if !cfg.subtract || cfg.measurement.clock() != Some(Clock::Wall) { return 0.0; };
+    let times: Vec<f64> = (0..timer::SAMPLES).map(|_| measure(cfg, || ()).time).collect();
+    times.medf_checked().expect("overhead calibration Nan detected")
+}
+
+/// Measurements per call of a batch of `batch` calls, except for the peak,
+/// which is that of the whole batch.
+/// The `overhead` is subtracted from the time of the whole batch.
+fn percall(overhead: f64, s: Sample, batch: usize) -> Sample {
+    let n = batch as f64;
+    Sample {
+        time: (s.time - overhead).max(0.0) / n,
+        second: s.second / n,
+        wall: s.wall / n,
+        allocs: s.allocs / n,
+        bytes: s.bytes / n,
+        peak: s.peak,
+    }
+}
+
+/// Auto repeats mode: true when the mad% of the `samples` times has dropped below `target`
+fn ontarget(target: f64, samples: &[Sample]) -> bool {
+    let times: Vec<f64> = samples.iter().map(|s| s.time).collect();
+    times.medf_checked().is_ok_and(|med| 100.0*times.madf(med) <= target*med)
+}
+
+/// Warm-up runs followed by the timed runs of one closure:
+/// `repeats` of them, continued in the auto mode until `cfg.target` mad% is reached,
+/// or the budget or `cfg.maxrepeats` is exhausted.
+/// `timeit(batch)` runs a batch of calls of the closure and returns their total measurements.
+/// Returns the measurements per call, less the `overhead` (see `percall`).
+fn timeruns(
+    cfg: &Config,
     repeats: usize,
-    names: &[&str],
-    closures: &[fn(&[u8])],
-) {
-    let algno = names.len();
-    heading("&[u8]",lengths.start,lengths.end,step,1,repeats); 
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let data = ranv_u8(d).expect("ranv_u8 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&data);
-                times.push(now.elapsed().as_nanos() as f64); 
-            };
-            let med = times.medf_checked().expect("benchu8 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med)); 
+    batch: usize,
+    overhead: f64,
+    mut timeit: impl FnMut(usize) -> Sample,
+) -> Vec<Sample> {
+    let mut run = || percall(overhead, timeit(batch), batch);
+    for _ in 0..cfg.warmup { run(); };
+    let mut samples: Vec<Sample> = Vec::with_capacity(repeats);
+    let start = Instant::now();
+    for _ in 0..repeats {
+        samples.push(run());
+    };
+    if let Some(target) = cfg.target {
+        while samples.len() < cfg.maxrepeats && start.elapsed() < cfg.budget && !ontarget(target, &samples) {
+            samples.push(run());
+        };
+    };
+    samples
+}
+
+/// Interleaved runs of all the closures, as `timeruns` of each, but in rounds:
+/// in each round, every closure runs once, in a random order, on its own copy of the same data.
+/// The auto mode continues until all the closures reach the target,
+/// or the budget of all of them or `cfg.maxrepeats` is exhausted.
+/// `timeit(closure,batch,call,rng)` runs a batch of calls of the closure on the data
+/// generated from `rng`, starting with the call number `call`.
+/// The order and the data of the rounds are drawn from `seed`.
+/// Returns the measurements per call of each closure, less the `overhead`.
+fn interleave(
+    cfg: &Config,
+    repeats: usize,
+    batches: &[usize],
+    seed: u64,
+    overhead: f64,
+    mut timeit: impl FnMut(usize, usize, usize, &mut Rng) -> Sample,
+) -> Vec<Vec<Sample>> {
+    let mut rounds = Rng::new(seed);
+    let stride = batches.iter().copied().max().unwrap_or(1);
+    let mut samples: Vec<Vec<Sample>> = vec![Vec::with_capacity(repeats); batches.len()];
+    let mut order: Vec<usize> = (0..batches.len()).collect();
+    let budget = cfg.budget * batches.len() as u32;
+    let mut start = Instant::now();
+    for round in 0.. {
+        let timed = round >= cfg.warmup;
+        if round == cfg.warmup { start = Instant::now(); };
+        if timed && round - cfg.warmup >= repeats {
+            let Some(target) = cfg.target else { break; };
+            if round - cfg.warmup >= cfg.maxrepeats || start.elapsed() >= budget
+                || samples.iter().all(|s| ontarget(target, s)) { break; };
+        };
+        let seed = rounds.seed();
+        // shuffle the order of the closures (Fisher-Yates)
+        for k in (1..order.len()).rev() {
+            order.swap(k, rounds.index(k + 1));
+        };
+        for &j in &order {
+            // the same data for each closure
+            let sample = timeit(j, batches[j], round*stride, &mut Rng::new(seed));
+            if timed { samples[j].push(percall(overhead, sample, batches[j])); };
+        };
+    };
+    samples
+}
+/// Prints the heading of the benches on data, with the options of `cfg` that apply to them:
+/// the `threads` when scaling, and whether `verified`.
+/// Returns the overhead to subtract (see `options`)
+fn heading(cfg:&Config,br:&BenchReport,threads:&[usize],verified:bool) -> f64 {
+    head!(
+        "\n{YL}Data:{GR}{} {YL}lengths:{GR}{}-{} {YL}step:{GR}{} {YL}rows:{GR}{} {YL}repeats:{GR}{}",
+        br.data, br.lengths.start, br.lengths.end, br.step, br.rows, br.repeats
+    );
+    if cfg.dist != Distribution::Uniform { head!(" {YL}distribution:{GR}{}", cfg.dist); };
+    if verified { head!(" {YL}verified{GR}"); };
+    if cfg.interleave { head!(" {YL}interleaved{GR}"); };
+    if threads != [1] { head!(" {YL}threads:{GR}{threads:?}"); };
+    options(cfg)
+}
+
+/// Prints the non default options of `cfg` that apply to all the benches, ending the heading line,
+/// then the timer calibration. Returns the overhead to subtract (see `overhead`).
+fn options(cfg:&Config) -> f64 {
+    if cfg.warmup > 0 { head!(" {YL}warmup:{GR}{}", cfg.warmup); };
+    if let Some(target) = cfg.target {
+        head!(" {YL}auto to mad:{GR}{target}% {YL}budget:{GR}{:?}", cfg.budget);
+    };
+    if let Some(name) = &cfg.reference { head!(" {YL}paired with:{GR}{name}"); };
+    if let Some(batch) = cfg.batch { head!(" {YL}batched to:{GR}{batch:?}"); };
+    if cfg.measurement.clock() != Some(Clock::Wall) {
+        head!(" {YL}measurement:{GR}{}", cfg.measurement.name());
+    };
+    if let Some(m) = &cfg.secondary { head!(" {YL}also:{GR}{}", m.name()); };
+    headln!("{UN}");
+    let timer = timer::calibration();
+    let overhead = overhead(cfg);
+    headln!(
+        "{YL}Timer overhead:{GR}{:.0}ns{} {YL}resolution:{GR}{:.0}ns{UN}",
+        if overhead > 0.0 { overhead } else { timer.overhead },
+        if overhead > 0.0 { " (subtracted)" } else { "" },
+        timer.resolution
+    );
+    overhead
+}
+
+/// Acquires the process wide lock, held by every bench function for its whole run,
+/// so that the benches in concurrently running tests never overlap and compete for the cores.
+/// Benches waiting for the lock print a warning when `cfg.warnwait` is set.
+/// A panic in another bench (e.g. a failed verification) does not poison it.
+fn benchlock(cfg: &Config) -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    match LOCK.try_lock() {
+        Ok(guard) => guard,
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(TryLockError::WouldBlock) => {
+            if cfg.warnwait { eprintln!("{RD}Waiting for another bench to finish{UN}"); };
+            LOCK.lock().unwrap_or_else(PoisonError::into_inner)
         }
-        report(names, &meds, &stderrs);
     }
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn benchu16( 
+/// Tests of listed `closures` that take no or constant arguments, named in `names`
+/// `repeats` runs of each closure.
+pub fn bench(repeats: usize, names: &[&str], closures: &[fn()]) -> BenchReport {
+    benchfnmut(&Config::default(), repeats, names, &mut closures.to_vec())
+}
+
+/// Tests of listed `closures` that take no arguments, named in `names`.
+/// The closures may capture and mutate their environment, e.g.
+/// `&mut [&mut dyn FnMut()]` or `Vec<Box<dyn FnMut()>>`.
+/// Their returned values are consumed through `black_box`, so that their work is not optimised away.  
+/// `repeats` runs of each closure, more in the auto mode of `cfg`.
+pub fn benchfnmut<R, F: FnMut() -> R>(cfg: &Config, repeats: usize, names: &[&str], closures: &mut [F]) -> BenchReport {
+    checkreference(cfg, names);
+    let _lock = benchlock(cfg);
+    head!(
+        "\n{YL}Input Data: {GR}none {YL}repeats: {GR}{repeats}"
+    );
+    let overhead = options(cfg);
+    let mut algos = Vec::with_capacity(names.len());
+    let seed = get_seed(); // store the seed, whatever it is
+    for (&name,closure) in names.iter().zip(closures) {
+        let rss = allocs::maxrss().unwrap_or(0);
+        let mut timeit = |n| measure(cfg, || for _ in 0..n { black_box(closure()); });
+        let batch = batchsize(cfg, MAXBATCH, &mut timeit);
+        // reintialise random numbers generator to the same seed for each closure
+        set_seeds(seed);
+        let times = timeruns(cfg, repeats, batch, overhead, timeit);
+        let rss = allocs::maxrss().unwrap_or(0).saturating_sub(rss) as f64;
+        algos.push(AlgoStats::new(name, times, batch, rss, "bench Nan detected"));
+    }
+    let lr = LengthReport::new(0, seed, algos, cfg.reference.as_deref());
+    report(cfg, &lr);
+    BenchReport {
+        data: "none".to_string(),
+        lengths: 0..0,
+        step: 1,
+        rows: 0,
+        repeats,
+        seed,
+        results: vec![lr],
+    }
+}
+
+/// Generic bench of listed `closures`, named in `names`,
+/// on `&[T]` random data generated by `T::generate`.  
+/// Lengths range is iterated over by step.  
+/// `rows` is the number of Vecs in each input when `T` is `Vec<_>` (ignored otherwise).  
+/// `repeats` runs of each closure for each length of data, more in the auto mode of `cfg`.  
+/// The closures may capture and mutate their environment, e.g.
+/// `&mut [&mut dyn FnMut(&[T])]` or `Vec<Box<dyn FnMut(&[T])>>`.  
+/// The input is passed through `black_box` before timing and the returned values
+/// are consumed through `black_box`, so that read only closures are not optimised away.
+pub fn benchgen<T: DataGen, R, F: FnMut(&[T]) -> R>(
+    cfg: &Config,
+    rows: usize,
     lengths: Range<usize>,
     step: usize,
     repeats: usize,
     names: &[&str],
-    closures: &[fn(&[u16])],
-) {
-    let algno = names.len();
-    heading("&[u16]",lengths.start,lengths.end,step,1,repeats); 
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let data = ranv_u16(d).expect("ranv_u16 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&data);
-                times.push(now.elapsed().as_nanos() as f64);
-            };
-            let med = times.medf_checked().expect("benchu16 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med));
-        }
-        report(names, &meds, &stderrs);
-    }
+    closures: &mut [F],
+) -> BenchReport {
+    let br = BenchReport { data: format!("&[{}]",T::label()), lengths, step, rows, repeats, seed: 0, results: Vec::new() };
+    sweep(cfg, br, names, &[1], false, false, |run, inputs: Vec<Vec<T>>| {
+        let inputs = black_box(inputs);
+        let closure = &mut closures[run.closure];
+        measure(cfg, || for input in inputs.iter().cycle().take(run.batch) { black_box(closure(input)); })
+    })
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn benchu64(
+/// Thread counts of the scaling benches: 1, 2, 4, ... up to `available_parallelism`
+pub fn threadcounts() -> Vec<usize> {
+    let max = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut counts: Vec<usize> = (0..).map(|k| 1 << k).take_while(|&t| t < max).collect();
+    counts.push(max);
+    counts
+}
+
+/// Thread counts of `cfg`, sorted, starting with 1
+fn scalingthreads(cfg: &Config) -> Vec<usize> {
+    let mut counts = cfg.threads.clone().unwrap_or_else(threadcounts);
+    counts.push(1);
+    counts.retain(|&t| t > 0);
+    counts.sort_unstable();
+    counts.dedup();
+    counts
+}
+
+/// Scaling bench of parallel `closures`, named in `names`, on `&[T]` random data
+/// generated by `T::generate`. Each closure receives the number of threads to use
+/// along with the data and is run with each of the thread counts of `cfg.threads`
+/// (by default `threadcounts()`), reported as `name/threads`.
+/// The speedups and the parallel efficiencies are relative to the same closure with one thread.  
+/// Otherwise as `benchgen`.
+pub fn benchscaling<T: DataGen, R, F: FnMut(&[T], usize) -> R>(
+    cfg: &Config,
+    rows: usize,
     lengths: Range<usize>,
     step: usize,
     repeats: usize,
     names: &[&str],
-    closures: &[fn(&[u64])],
-) {
-    let algno = names.len();
-    heading("&[u64]",lengths.start,lengths.end,step,1,repeats);
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let data = ranv_u64(d).expect("ranv_u64 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&data);
-                times.push(now.elapsed().as_nanos() as f64);
-            };
-            let med = times.medf_checked().expect("benchu64 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med)); 
-        };
-        report(names, &meds, &stderrs);
-    }
+    closures: &mut [F],
+) -> BenchReport {
+    let br = BenchReport { data: format!("&[{}]",T::label()), lengths, step, rows, repeats, seed: 0, results: Vec::new() };
+    sweep(cfg, br, names, &scalingthreads(cfg), false, false, |run, inputs: Vec<Vec<T>>| {
+        let inputs = black_box(inputs);
+        let closure = &mut closures[run.closure];
+        measure(cfg, || for input in inputs.iter().cycle().take(run.batch) { black_box(closure(input, run.threads)); })
+    })
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn benchf64(
+/// Generic bench of listed `closures` that mutate their input, named in `names`,
+/// on `&mut [T]` random data generated by `T::generate`.  
+/// Lengths range is iterated over by step.  
+/// `rows` is the number of Vecs in each input when `T` is `Vec<_>` (ignored otherwise).  
+/// `repeats` runs of each closure for each length of data, more in the auto mode of `cfg`.  
+/// The closures may capture and mutate their environment, e.g.
+/// `&mut [&mut dyn FnMut(&mut [T])]` or `Vec<Box<dyn FnMut(&mut [T])>>`.  
+/// The input is passed through `black_box` before timing and the returned values
+/// are consumed through `black_box`.  
+/// With `cfg.verify`, the mutated outputs are checked against those of the first closure.
+pub fn mutbenchgen<T: DataGen, R, F: FnMut(&mut [T]) -> R>(
+    cfg: &Config,
+    rows: usize,
     lengths: Range<usize>,
     step: usize,
     repeats: usize,
     names: &[&str],
-    closures: &[fn(&[f64])],
-) {
-    let algno = names.len();
-    heading("&[f64]",lengths.start,lengths.end,step,1,repeats);  
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let data = ranv_f64(d).expect("ranv_f64 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&data);
-                times.push(now.elapsed().as_nanos() as f64);
-            };
-            let med = times.medf_checked().expect("benchf64 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med));
-        };
-        report(names, &meds, &stderrs);
-    }
+    closures: &mut [F],
+) -> BenchReport {
+    let mut closures = singlethreaded(closures);
+    mutsweep(cfg, rows, lengths, step, repeats, names, &[1], &mut closures, |_: &[T]| true)
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn benchvvu8(
-    points: usize, // number of Vecs in each Vec<Vec<u8>>
+/// As `mutbenchgen`, always verifying the correctness of the closures:
+/// the mutated output of every closure must equal that of the first closure
+/// on identical input, and must satisfy the `oracle`, e.g. "is sorted".
+/// Panics with the seed and length of the data when a closure fails.
+#[allow(clippy::too_many_arguments)]
+pub fn mutbenchverify<T: DataGen, R, F: FnMut(&mut [T]) -> R>(
+    cfg: &Config,
+    rows: usize,
     lengths: Range<usize>,
     step: usize,
     repeats: usize,
     names: &[&str],
-    closures: &[fn(&[Vec<u8>])],
-) {
-    let algno = names.len();
-    heading("&[Vec<u8>]",lengths.start,lengths.end,step,points,repeats); 
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let data = ranvv_u8(points,d).expect("ranvv_u8 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&data);
-                times.push(now.elapsed().as_nanos() as f64);
-            };
-            let med = times.medf_checked().expect("benchvvu8 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med));
-        };
-        report(names, &meds, &stderrs);
-    }
+    closures: &mut [F],
+    oracle: impl Fn(&[T]) -> bool,
+) -> BenchReport {
+    let cfg = Config { verify: true, ..cfg.clone() };
+    let mut closures = singlethreaded(closures);
+    mutsweep(&cfg, rows, lengths, step, repeats, names, &[1], &mut closures, oracle)
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn benchvvu16(
-    points: usize, // number of Vecs in each Vec<Vec<u8>>
+/// Scaling bench of parallel `closures` that mutate their input, named in `names`,
+/// on `&mut [T]` random data generated by `T::generate`.
+/// Each closure receives the number of threads to use along with the data,
+/// as in `benchscaling`. Otherwise as `mutbenchgen`: with `cfg.verify`, the outputs
+/// of all the thread counts are checked against those of the first closure with one thread.
+pub fn mutbenchscaling<T: DataGen, R, F: FnMut(&mut [T], usize) -> R>(
+    cfg: &Config,
+    rows: usize,
     lengths: Range<usize>,
     step: usize,
     repeats: usize,
     names: &[&str],
-    closures: &[fn(&[Vec<u16>])],
-) {
-    let algno = names.len();
-    heading("&[Vec<u8>]",lengths.start,lengths.end,step,points,repeats); 
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let data = ranvv_u16(points,d).expect("ranvv_u16 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&data);
-                times.push(now.elapsed().as_nanos() as f64);
-            };
-            let med = times.medf_checked().expect("benchvvu16 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med));
-        };
-        report(names, &meds, &stderrs);
-    }
+    closures: &mut [F],
+) -> BenchReport {
+    mutsweep(cfg, rows, lengths, step, repeats, names, &scalingthreads(cfg), closures, |_: &[T]| true)
+}
+
+/// Adapts single threaded `closures` to the closures of `mutsweep`, ignoring the thread count
+fn singlethreaded<T, R, F: FnMut(&mut [T]) -> R>(closures: &mut [F]) -> Vec<impl FnMut(&mut [T], usize) -> R + '_> {
+    closures.iter_mut().map(|f| move |v: &mut [T], _: usize| f(v)).collect()
+}
+
+/// Message of the panic on a `Config::reference` naming none of the closures
+fn noreference(name: &str) -> String {
+    format!("Config::reference: no closure named {name}")
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn benchvvf64( 
-    points: usize, // number of Vecs in each Vec<Vec<u8>>
+/// Panics when `cfg.reference` is set and is none of the `names`
+fn checkreference(cfg: &Config, names: &[&str]) {
+    if let Some(name) = &cfg.reference {
+        assert!(names.contains(&name.as_str()), "{}", noreference(name));
+    };
+}
+
+/// Common part of `mutbenchgen`, `mutbenchverify` and `mutbenchscaling`
+#[allow(clippy::too_many_arguments)]
+fn mutsweep<T: DataGen, R, F: FnMut(&mut [T], usize) -> R, O: Fn(&[T]) -> bool>(
+    cfg: &Config,
+    rows: usize,
     lengths: Range<usize>,
     step: usize,
     repeats: usize,
     names: &[&str],
-    closures: &[fn(&[Vec<f64>])],
-) {
-    let algno = names.len();
-    heading("&[Vec<f64>]",lengths.start,lengths.end,step,points,repeats);  
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let data = ranvv_f64(points,d).expect("ranvv_f64 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&data);
-                times.push(now.elapsed().as_nanos() as f64);
+    threads: &[usize],
+    closures: &mut [F],
+    oracle: O,
+) -> BenchReport {
+    let br = BenchReport { data: format!("&mut[{}]",T::label()), lengths, step, rows, repeats, seed: 0, results: Vec::new() };
+    // the fingerprint of the first output for each call number at the current length,
+    // with the closure that made it
+    let mut reference: HashMap<usize, (usize, u64)> = HashMap::new();
+    let mut reflength = None;
+    sweep(cfg, br, names, threads, true, cfg.verify, |run, inputs: Vec<Vec<T>>| {
+        let mut inputs = black_box(inputs);
+        let closure = &mut closures[run.closure];
+        let sample = measure(cfg, || for input in &mut inputs { black_box(closure(input, run.threads)); });
+        if let (true, Some(first)) = (cfg.verify, run.call) {
+            if reflength != Some(run.length) {
+                reference.clear();
+                reflength = Some(run.length);
+            };
+            for (call, data) in (first..).zip(inputs) {
+                let fail = |what: &str| -> ! {
+                    panic!(
+                        "{} {what} at length: {}, seed: {}, run: {call}",
+                        names[run.closure], run.length, run.seed
+                    )
+                };
+                if !oracle(&data) {
+                    fail("output failed the oracle");
+                };
+                let fingerprint = T::fingerprint(&data);
+                match reference.get(&call) {
+                    None => { reference.insert(call, (run.closure, fingerprint)); },
+                    Some((other, r)) if *r != fingerprint => {
+                        fail(&format!("output differs from that of {}", names[*other]));
+                    },
+                    _ => (),
+                };
             };
-            let med = times.medf_checked().expect("benchvvf64 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med));
         };
-        report(names, &meds, &stderrs);
-    }
+        sample
+    })
 }
 
-/// Tests of listed `closures`, named in `names`,
-/// on random data vectors of type specified by `rn`
-/// and lengths of increasing `magnitudes` in multiples of 10, e.g. 10,100,1000  
-/// `repeats` runs of each closure for each magnitude
-pub fn benchvvu64( 
-    points: usize, // number of Vecs in each Vec<Vec<u64>>
-    lengths: Range<usize>,
-    step: usize,
-    repeats: usize,
+/// Identifies one run of one closure within a `sweep`
+struct Run {
+    /// Index of the closure
+    closure: usize,
+    /// Number of threads for the closure (1 unless scaling)
+    threads: usize,
+    /// Length of the data
+    length: usize,
+    /// Seed of the data for this length
+    seed: u64,
+    /// Number of the first call in this run, counting the warm-up runs, from 0.
+    /// Calls with the same number get the same data for all the closures.
+    /// None for the batch size calibration runs.
+    call: Option<usize>,
+    /// Number of calls in this run, cycling through the inputs when there are fewer of them
+    batch: usize,
+}
+
+/// The engine of all the bench functions on data:
+/// fills in the `results` of `br` over its `lengths`, `rows` and `repeats`,
+/// running each closure with each of the `threads` counts (just `&[1]` unless scaling).
+/// `verified` is whether `timeit` verifies the outputs, for the heading.
+/// `timeit(run,inputs)` runs the batch of calls of `run` on the pre-generated `inputs`
+/// and returns their total measurements. There is one input per call when `mutating`,
+/// otherwise at most `POOL` of them. Either way, the batches keep within `MAXBYTES` of inputs.
+fn sweep<T: DataGen>(
+    cfg: &Config,
+    mut br: BenchReport,
     names: &[&str],
-    closures: &[fn(&[Vec<u64>])],
-) {
-    let algno = names.len();
-    heading("&[Vec<u64>]",lengths.start,lengths.end,step,points,repeats); 
-    for d in lengths.step_by(step) {
-        let mut meds = Vec::with_capacity(algno);
-        let mut stderrs = Vec::with_capacity(algno);
-        println!("\nLength: {BL}{}{UN}\n", d);
-        let seed = get_seed(); // store the seed, whatever it is
-        for closure in closures {
-            // reintialise random numbers generator to the same seed for each closure
-            set_seeds(seed);
-            let mut times: Vec<f64> = Vec::with_capacity(repeats);
-            for _ in 0..repeats {
-                let data = ranvv_u64(points,d).expect("ranvv_u64 failed"); // different for each repeat
-                let now = Instant::now();
-                closure(&data);
-                times.push(now.elapsed().as_nanos() as f64);
-            };
-            let med = times.medf_checked().expect("benchvvu64 Nan detected");
-            meds.push(med);
-            stderrs.push(times.madf(med));
+    threads: &[usize],
+    mutating: bool,
+    verified: bool,
+    mut timeit: impl FnMut(&Run, Vec<Vec<T>>) -> Sample,
+) -> BenchReport {
+    checkreference(cfg, names);
+    let _lock = benchlock(cfg);
+    let overhead = heading(cfg, &br, threads, verified);
+    let scaling = threads != [1];
+    // (closure, threads) of each measured entry
+    let entries: Vec<(usize, usize)> = (0..names.len()).flat_map(|i| threads.iter().map(move |&t| (i, t))).collect();
+    br.seed = if cfg.seed == 0 { rng::fresh_seed() } else { cfg.seed };
+    // seeds of the lengths
+    let mut lengths = Rng::new(br.seed);
+    for d in br.lengths.clone().step_by(br.step) {
+        outln!("\nLength: {BL}{}{UN}\n", d);
+        let seed = lengths.seed();
+        // the number of inputs within the memory budget of a batch
+        let affordable = (MAXBYTES / T::bytes(br.rows, d).max(1)).clamp(1, MAXBATCH);
+        let mut run = |j: usize, n: usize, call: Option<usize>, rng: &mut Rng| {
+            let (closure, threads) = entries[j];
+            let inputs = if mutating { n } else { n.min(POOL).min(affordable) };
+            let inputs = (0..inputs).map(|_| T::generate(br.rows, d, &cfg.dist, rng)).collect();
+            timeit(&Run { closure, threads, length: d, seed, call, batch: n }, inputs)
+        };
+        let maxbatch = if mutating { affordable } else { MAXBATCH };
+        let batches: Vec<usize> = (0..entries.len())
+            .map(|j| {
+                let mut rng = Rng::new(seed);
+                batchsize(cfg, maxbatch, |n| run(j, n, None, &mut rng))
+            })
+            .collect();
+        let mut rss = vec![0_usize; entries.len()];
+        let runs = if cfg.interleave {
+            interleave(cfg, br.repeats, &batches, seed, overhead, |j, n, call, rng| {
+                let before = allocs::maxrss().unwrap_or(0);
+                let sample = run(j, n, Some(call), rng);
+                rss[j] += allocs::maxrss().unwrap_or(0).saturating_sub(before);
+                sample
+            })
+        } else {
+            // repeats start at a common stride of calls, as in `interleave`,
+            // so that the closures with different batch sizes still pair up on the same data
+            let stride = batches.iter().copied().max().unwrap_or(1);
+            (0..entries.len()).map(|j| {
+                let before = allocs::maxrss().unwrap_or(0);
+                // the same sequence of data seeds for each closure
+                let mut rounds = Rng::new(seed);
+                let mut round = 0;
+                // different data for each repeat
+                let samples = timeruns(cfg, br.repeats, batches[j], overhead, |n| {
+                    let sample = run(j, n, Some(round*stride), &mut Rng::new(rounds.seed()));
+                    round += 1;
+                    sample
+                });
+                rss[j] = allocs::maxrss().unwrap_or(0).saturating_sub(before);
+                samples
+            }).collect()
         };
-        report(names, &meds, &stderrs);
+        let msg = format!("{} Nan detected",br.data);
+        let mut algos: Vec<AlgoStats> = Vec::with_capacity(entries.len());
+        for (j, samples) in runs.into_iter().enumerate() {
+            let (i, t) = entries[j];
+            if scaling {
+                let mut algo = AlgoStats::new(&format!("{}/{t}", names[i]), samples, batches[j], rss[j] as f64, &msg);
+                algo.threads = t;
+                // threads are sorted, so the first one of each closure has one thread
+                algo.speedup = algos[i*threads.len()..].first().map_or(1.0, |one| one.median / algo.median);
+                algos.push(algo);
+            } else {
+                algos.push(AlgoStats::new(names[i], samples, batches[j], rss[j] as f64, &msg));
+            };
+        }
+        let lr = LengthReport::new(d, seed, algos, cfg.reference.as_deref());
+        report(cfg, &lr);
+        br.results.push(lr);
     }
+    br
+}
+
+/// Generates the thin typed wrappers around `benchgen` and `mutbenchgen`
+macro_rules! typed_benches {
+    ($($t:ty: $bench:ident, $mutbench:ident, $benchvv:ident, $mutbenchvv:ident;)*) => {$(
+        #[doc = concat!("Tests of listed `closures`, named in `names`, on random `&[", stringify!($t), "]` data.  ")]
+        #[doc = "Lengths range is iterated over by step.  "]
+        #[doc = "`repeats` runs of each closure for each length of data.  "]
+        #[doc = "The returned values are consumed through `black_box`, so that their work is not optimised away."]
+        pub fn $bench<R>(
+            lengths: Range<usize>,
+            step: usize,
+            repeats: usize,
+            names: &[&str],
+            closures: &[fn(&[$t]) -> R],
+        ) -> BenchReport {
+            benchgen(&Config::default(), 1, lengths, step, repeats, names, &mut closures.to_vec())
+        }
+
+        #[doc = concat!("Tests of listed `closures` that mutate their input, named in `names`, on random `&mut [", stringify!($t), "]` data.  ")]
+        #[doc = "Lengths range is iterated over by step.  "]
+        #[doc = "`repeats` runs of each closure for each length of data.  "]
+        #[doc = "The returned values are consumed through `black_box`, so that their work is not optimised away."]
+        pub fn $mutbench<R>(
+            lengths: Range<usize>,
+            step: usize,
+            repeats: usize,
+            names: &[&str],
+            closures: &[fn(&mut [$t]) -> R],
+        ) -> BenchReport {
+            mutbenchgen(&Config::default(), 1, lengths, step, repeats, names, &mut closures.to_vec())
+        }
+
+        #[doc = concat!("Tests of listed `closures`, named in `names`, on random `&[Vec<", stringify!($t), ">]` data")]
+        #[doc = "of `points` Vecs each.  "]
+        #[doc = "Lengths range of the Vecs is iterated over by step.  "]
+        #[doc = "`repeats` runs of each closure for each length of data.  "]
+        #[doc = "The returned values are consumed through `black_box`, so that their work is not optimised away."]
+        pub fn $benchvv<R>(
+            points: usize,
+            lengths: Range<usize>,
+            step: usize,
+            repeats: usize,
+            names: &[&str],
+            closures: &[fn(&[Vec<$t>]) -> R],
+        ) -> BenchReport {
+            benchgen(&Config::default(), points, lengths, step, repeats, names, &mut closures.to_vec())
+        }
+
+        #[doc = concat!("Tests of listed `closures` that mutate their input, named in `names`, on random `&mut [Vec<", stringify!($t), ">]` data")]
+        #[doc = "of `points` Vecs each.  "]
+        #[doc = "Lengths range of the Vecs is iterated over by step.  "]
+        #[doc = "`repeats` runs of each closure for each length of data.  "]
+        #[doc = "The returned values are consumed through `black_box`, so that their work is not optimised away."]
+        pub fn $mutbenchvv<R>(
+            points: usize,
+            lengths: Range<usize>,
+            step: usize,
+            repeats: usize,
+            names: &[&str],
+            closures: &[fn(&mut [Vec<$t>]) -> R],
+        ) -> BenchReport {
+            mutbenchgen(&Config::default(), points, lengths, step, repeats, names, &mut closures.to_vec())
+        }
+    )*};
+}
+
+typed_benches! {
+    u8: benchu8, mutbenchu8, benchvvu8, mutbenchvvu8;
+    u16: benchu16, mutbenchu16, benchvvu16, mutbenchvvu16;
+    u32: benchu32, mutbenchu32, benchvvu32, mutbenchvvu32;
+    u64: benchu64, mutbenchu64, benchvvu64, mutbenchvvu64;
+    u128: benchu128, mutbenchu128, benchvvu128, mutbenchvvu128;
+    usize: benchusize, mutbenchusize, benchvvusize, mutbenchvvusize;
+    i8: benchi8, mutbenchi8, benchvvi8, mutbenchvvi8;
+    i16: benchi16, mutbenchi16, benchvvi16, mutbenchvvi16;
+    i32: benchi32, mutbenchi32, benchvvi32, mutbenchvvi32;
+    i64: benchi64, mutbenchi64, benchvvi64, mutbenchvvi64;
+    f32: benchf32, mutbenchf32, benchvvf32, mutbenchvvf32;
+    f64: benchf64, mutbenchf64, benchvvf64, mutbenchvvf64;
 }
@@ -0,0 +1,148 @@
+use crate::{AlgoStats, BenchReport, LengthReport};
+use std::{fmt::Write as _, fs, io, path::Path};
+
+/// Quoted and escaped JSON string
+fn jstr(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                write!(res, "\\u{:04x}", c as u32).ok();
+            }
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// JSON number, `null` when not finite
+fn jnum(x: f64) -> String {
+    if x.is_finite() {
+        format!("{x}")
+    } else {
+        "null".to_string()
+    }
+}
+
+impl AlgoStats {
+    /// JSON object of these statistics, `ratio` is relative to the fastest closure
+    fn to_json(&self, ratio: f64) -> String {
+        format!(
+            "{{\"name\":{},\"median\":{},\"mad\":{},\"ratio\":{},\"group\":{},\"pvalue\":{},\"samples\":{},\"batch\":{},\"second\":{},\"threads\":{},\"speedup\":{},\"paired\":{},\"pairedmad\":{},\"allocs\":{},\"bytes\":{},\"peak\":{},\"maxrss\":{},\"times\":[{}]}}",
+            jstr(&self.name),
+            jnum(self.median),
+            jnum(self.mad),
+            jnum(ratio),
+            self.group,
+            jnum(self.pvalue),
+            self.samples,
+            self.batch,
+            jnum(self.second),
+            self.threads,
+            jnum(self.speedup),
+            jnum(self.paired),
+            jnum(self.pairedmad),
+            jnum(self.allocs),
+            jnum(self.bytes),
+            jnum(self.peak),
+            jnum(self.maxrss),
+            self.times.iter().map(|&t| jnum(t)).collect::<Vec<String>>().join(",")
+        )
+    }
+}
+
+impl LengthReport {
+    /// JSON object of the results for this length
+    fn to_json(&self) -> String {
+        // no ratios without closures
+        let fastest = if self.algos.is_empty() { f64::NAN } else { self.fastest().median };
+        format!(
+            "{{\"length\":{},\"seed\":{},\"algos\":[\n{}\n]}}",
+            self.length,
+            self.seed,
+            self.algos
+                .iter()
+                .map(|a| a.to_json(a.median / fastest))
+                .collect::<Vec<String>>()
+                .join(",\n")
+        )
+    }
+}
+
+impl BenchReport {
+    /// The whole report as a JSON document:
+    /// the heading metadata followed by the results for each length.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"data\":{},\"lengths\":[{},{}],\"step\":{},\"rows\":{},\"repeats\":{},\"seed\":{},\"results\":[\n{}\n]}}\n",
+            jstr(&self.data),
+            self.lengths.start,
+            self.lengths.end,
+            self.step,
+            self.rows,
+            self.repeats,
+            self.seed,
+            self.results
+                .iter()
+                .map(|r| r.to_json())
+                .collect::<Vec<String>>()
+                .join(",\n")
+        )
+    }
+
+    /// Writes the JSON document (see `to_json`) to file `path`
+    pub fn write_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+}
+
+/// CSV field, quoted when necessary
+fn csvfield(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl BenchReport {
+    /// Header line of the CSV output
+    pub const CSV_HEADER: &'static str = "data,length,algorithm,median,mad,madpc,ratio";
+
+    /// The whole report as CSV text, with the header line followed by
+    /// one row per (data type, length, algorithm) in the order of `names`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(Self::CSV_HEADER);
+        csv.push('\n');
+        let data = csvfield(&self.data);
+        for r in self.results.iter().filter(|r| !r.algos.is_empty()) {
+            let fastest = r.fastest().median;
+            for a in &r.algos {
+                writeln!(
+                    csv,
+                    "{data},{},{},{},{},{},{}",
+                    r.length,
+                    csvfield(&a.name),
+                    a.median,
+                    a.mad,
+                    a.madpc(),
+                    a.median / fastest
+                )
+                .ok();
+            }
+        }
+        csv
+    }
+
+    /// Writes the CSV text (see `to_csv`) to file `path`
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+}
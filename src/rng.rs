@@ -0,0 +1,88 @@
+use std::{
+    ops::RangeInclusive,
+    sync::atomic::{AtomicU64, Ordering},
+    time::UNIX_EPOCH,
+};
+
+/// Owned random numbers generator of the bench engine: xoshiro256**,
+/// adapted from the algorithm by David Blackman and Sebastiano Vigna, 2018.
+/// Each run seeds its own generator explicitly, so its data is reproducible
+/// regardless of the other threads and of the closures' own use of `ran`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+    s: [u64; 4],
+}
+
+/// SplitMix64 step, for expanding one seed into the xoshiro state
+fn splitmix(x: &mut u64) -> u64 {
+    *x = x.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// New essentially unpredictable seed, different for each call
+pub fn fresh_seed() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut x = UNIX_EPOCH.elapsed().map_or(0, |t| t.as_nanos() as u64)
+        ^ COUNTER.fetch_add(1, Ordering::Relaxed).rotate_left(32);
+    splitmix(&mut x).max(1)
+}
+
+impl Rng {
+    /// Generator reproducing the same sequence for each `seed`.
+    /// Seed 0 gets a `fresh_seed` instead.
+    pub fn new(seed: u64) -> Self {
+        let mut x = if seed == 0 { fresh_seed() } else { seed };
+        Rng { s: [splitmix(&mut x), splitmix(&mut x), splitmix(&mut x), splitmix(&mut x)] }
+    }
+    /// Random u64
+    pub fn u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+    /// Random number of the given number of (the top) bits, 1..=64
+    pub fn ubits(&mut self, bits: u32) -> u64 {
+        self.u64() >> (64 - bits)
+    }
+    /// Random u8
+    pub fn u8(&mut self) -> u8 {
+        self.ubits(8) as u8
+    }
+    /// Random u16
+    pub fn u16(&mut self) -> u16 {
+        self.ubits(16) as u16
+    }
+    /// Random i64
+    pub fn i64(&mut self) -> i64 {
+        self.u64() as i64
+    }
+    /// Random f64 in [0,1)
+    pub fn f64(&mut self) -> f64 {
+        (self.u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+    /// Random u64 in the inclusive range
+    pub fn range(&mut self, r: RangeInclusive<u64>) -> u64 {
+        match (r.end() - r.start()).checked_add(1) {
+            Some(span) => r.start() + self.u64() % span,
+            None => self.u64(),
+        }
+    }
+    /// Nonzero seed for a new generator, independent of this one
+    pub fn seed(&mut self) -> u64 {
+        self.u64().max(1)
+    }
+    /// Random index in 0..len, len > 0
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0..=(len as u64 - 1)) as usize
+    }
+}
@@ -0,0 +1,189 @@
+use crate::BenchReport;
+use indxvec::{printing::*, Vecops};
+use medians::Medianf64;
+use std::fmt;
+
+/// Significance level of the tests, below which the difference is significant
+pub const ALPHA: f64 = 0.05;
+
+/// Standard normal cumulative distribution function.
+/// Uses the erfc approximation 7.1.26 of Abramowitz and Stegun, max error 1.5e-7.
+pub fn normcdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let erfc = t
+        * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))))
+        * (-x * x).exp();
+    if z >= 0.0 {
+        1.0 - 0.5 * erfc
+    } else {
+        0.5 * erfc
+    }
+}
+
+/// Mann-Whitney U rank test of two independent samples.
+/// Returns the two sided p-value, using the normal approximation
+/// with ties and continuity corrections.
+/// Values below `ALPHA` mean that the two samples are significantly different.
+pub fn mannwhitney(a: &[f64], b: &[f64]) -> f64 {
+    let (n1, n2) = (a.len(), b.len());
+    if n1 == 0 || n2 == 0 {
+        return 1.0;
+    };
+    let n = n1 + n2;
+    let all = [a, b].concat();
+    let sortx = all.isort_indexed(0..n, |x: &f64, y| x.total_cmp(y));
+    // mid-ranks (1 based), averaged over ties
+    let mut ranks = vec![0_f64; n];
+    let mut tiesum = 0_f64;
+    let mut i = 0;
+    while i < n {
+        let mut j = i + 1;
+        while j < n && all[sortx[j]] == all[sortx[i]] {
+            j += 1;
+        }
+        let midrank = (i + j + 1) as f64 / 2.0;
+        for &k in &sortx[i..j] {
+            ranks[k] = midrank;
+        }
+        let t = (j - i) as f64;
+        tiesum += t * t * t - t;
+        i = j;
+    }
+    let (n1, n2, n) = (n1 as f64, n2 as f64, n as f64);
+    let r1: f64 = ranks[..a.len()].iter().sum();
+    let u = r1 - n1 * (n1 + 1.0) / 2.0;
+    let mu = n1 * n2 / 2.0;
+    let sigma = (n1 * n2 / 12.0 * ((n + 1.0) - tiesum / (n * (n - 1.0)))).sqrt();
+    if sigma == 0.0 || !sigma.is_finite() {
+        return 1.0;
+    };
+    let z = ((u - mu).abs() - 0.5).max(0.0) / sigma;
+    (2.0 * (1.0 - normcdf(z))).min(1.0)
+}
+
+/// Paired comparison of `times` against the `reference` times taken on the same data,
+/// repeat by repeat: the speedups `reference[k]/times[k]`.
+/// Returns their median and mad, NaN when there are no finite speedups.
+/// Much tighter than comparing two independent medians, as the variations
+/// of the data and of the machine affect both members of each pair alike.
+pub fn pairedspeedup(reference: &[f64], times: &[f64]) -> (f64, f64) {
+    let speedups: Vec<f64> = reference
+        .iter()
+        .zip(times)
+        .map(|(r, t)| r / t)
+        .filter(|s| s.is_finite())
+        .collect();
+    match speedups.medf_checked() {
+        Ok(median) => (median, speedups.madf(median)),
+        Err(_) => (f64::NAN, f64::NAN),
+    }
+}
+
+/// Candidate complexity models of execution time as a function of data length n
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Model {
+    /// c*n
+    Linear,
+    /// c*n*log2(n)
+    NLogN,
+    /// c*n^2
+    Quadratic,
+}
+
+impl Model {
+    /// All the candidate models
+    pub const ALL: [Model; 3] = [Model::Linear, Model::NLogN, Model::Quadratic];
+    /// Value of the model function (without the constant) at length `n`
+    pub fn eval(self, n: f64) -> f64 {
+        match self {
+            Model::Linear => n,
+            Model::NLogN => n * n.log2(),
+            Model::Quadratic => n * n,
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Model::Linear => write!(f, "n"),
+            Model::NLogN => write!(f, "n log n"),
+            Model::Quadratic => write!(f, "n^2"),
+        }
+    }
+}
+
+/// Best fitting complexity model of one algorithm's medians across the lengths sweep
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fit {
+    /// Name of the algorithm
+    pub name: String,
+    /// The best fitting model
+    pub model: Model,
+    /// Constant factor c of the best model, in the unit of `Config::measurement`
+    /// (nanoseconds for the clocks)
+    pub constant: f64,
+    /// Root mean square relative error of the best model
+    pub error: f64,
+    /// Slope of log(time) against log(n), i.e. the empirical exponent of n
+    pub slope: f64,
+}
+
+/// Least squares fit of `times` = c*model(`lengths`), minimising the relative errors.
+/// Returns the constant c and the root mean square relative error.
+pub fn fitmodel(model: Model, lengths: &[f64], times: &[f64]) -> (f64, f64) {
+    let ratios: Vec<f64> = lengths.iter().zip(times).map(|(&n, &t)| model.eval(n) / t).collect();
+    let c = ratios.iter().sum::<f64>() / ratios.iter().map(|r| r * r).sum::<f64>();
+    let sumsq: f64 = ratios.iter().map(|r| (1.0 - c * r).powi(2)).sum();
+    (c, (sumsq / ratios.len() as f64).sqrt())
+}
+
+/// Least squares slope of log(`times`) against log(`lengths`)
+pub fn loglogslope(lengths: &[f64], times: &[f64]) -> f64 {
+    let x: Vec<f64> = lengths.iter().map(|n| n.ln()).collect();
+    let y: Vec<f64> = times.iter().map(|t| t.ln()).collect();
+    let k = x.len() as f64;
+    let (mx, my) = (x.iter().sum::<f64>() / k, y.iter().sum::<f64>() / k);
+    let sxy: f64 = x.iter().zip(&y).map(|(a, b)| (a - mx) * (b - my)).sum();
+    let sxx: f64 = x.iter().map(|a| (a - mx).powi(2)).sum();
+    sxy / sxx
+}
+
+impl BenchReport {
+    /// Fits each algorithm's medians across the lengths sweep against the candidate
+    /// complexity `Model`s, prints and returns the best fitting model with its constant factor,
+    /// together with the log-log slope.
+    /// Needs at least two different positive lengths, otherwise returns no fits.
+    pub fn complexity(&self) -> Vec<Fit> {
+        let results: Vec<_> = self.results.iter().filter(|r| r.length > 1).collect();
+        if results.len() < 2 {
+            return Vec::new();
+        };
+        outln!("\n{YL}Complexity fits, data:{GR}{}{UN}\n", self.data);
+        let lengths: Vec<f64> = results.iter().map(|r| r.length as f64).collect();
+        let mut fits = Vec::new();
+        for (i, algo) in results[0].algos.iter().enumerate() {
+            let times: Vec<f64> = results.iter().map(|r| r.algos[i].median).collect();
+            let (model, constant, error) = Model::ALL
+                .iter()
+                .map(|&m| {
+                    let (c, e) = fitmodel(m, &lengths, &times);
+                    (m, c, e)
+                })
+                .min_by(|a, b| a.2.total_cmp(&b.2))
+                .expect("no complexity models");
+            let slope = loglogslope(&lengths, &times);
+            outln!(
+                "{YL}{:<18}{GR}{:>10.4} * {:<8} {YL}error:{GR}{:>6.2}% {YL}log-log slope:{GR}{:>6.3}{UN}",
+                algo.name,
+                constant,
+                model.to_string(),
+                100.0 * error,
+                slope
+            );
+            fits.push(Fit { name: algo.name.clone(), model, constant, error, slope });
+        }
+        fits
+    }
+}
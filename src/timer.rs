@@ -0,0 +1,123 @@
+use crate::Measurement;
+use medians::Medianf64;
+use std::{fmt, sync::OnceLock, time::Instant};
+
+/// Clocks of the time measurements, for `Config::measurement` and `Config::secondary`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Clock {
+    /// Wall clock time, by `Instant`
+    #[default]
+    Wall,
+    /// CPU time of the benchmarking thread, by `CLOCK_THREAD_CPUTIME_ID` (unix only)
+    ThreadCpu,
+    /// CPU time of the whole process, by `CLOCK_PROCESS_CPUTIME_ID` (unix only)
+    ProcessCpu,
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Clock::Wall => write!(f, "wall"),
+            Clock::ThreadCpu => write!(f, "thread cpu"),
+            Clock::ProcessCpu => write!(f, "process cpu"),
+        }
+    }
+}
+
+impl Clock {
+    /// Current reading of this clock in nanoseconds, from an arbitrary origin.
+    /// Panics for the CPU clocks on the platforms other than unix.
+    pub fn now(self) -> f64 {
+        match self {
+            Clock::Wall => {
+                static ORIGIN: OnceLock<Instant> = OnceLock::new();
+                ORIGIN.get_or_init(Instant::now).elapsed().as_nanos() as f64
+            }
+            Clock::ThreadCpu => cputime(Clock::ThreadCpu),
+            Clock::ProcessCpu => cputime(Clock::ProcessCpu),
+        }
+    }
+}
+
+impl Measurement for Clock {
+    fn name(&self) -> String {
+        self.to_string()
+    }
+    fn unit(&self) -> String {
+        "ns".to_string()
+    }
+    fn start(&self) -> f64 {
+        self.now()
+    }
+    fn clock(&self) -> Option<Clock> {
+        Some(*self)
+    }
+}
+
+/// Reading of the CPU time `clock` in nanoseconds
+#[cfg(unix)]
+fn cputime(clock: Clock) -> f64 {
+    let id = if clock == Clock::ThreadCpu {
+        libc::CLOCK_THREAD_CPUTIME_ID
+    } else {
+        libc::CLOCK_PROCESS_CPUTIME_ID
+    };
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: clock_gettime only writes into the provided timespec
+    let res = unsafe { libc::clock_gettime(id, &mut ts) };
+    assert_eq!(res, 0, "clock_gettime of {clock} clock failed");
+    ts.tv_sec as f64 * 1e9 + ts.tv_nsec as f64
+}
+
+/// Reading of the CPU time `clock` in nanoseconds
+#[cfg(not(unix))]
+fn cputime(clock: Clock) -> f64 {
+    panic!("{clock} clock is only available on unix")
+}
+
+/// Wall time exceeding the thread CPU time by more than this factor
+/// means that the closure was descheduled rather than slow
+pub const DESCHEDULED: f64 = 1.5;
+
+/// Measured costs of the `Instant` clock used for timing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Calibration {
+    /// Median time in nanoseconds of timing nothing, i.e. of `Instant::now()` and `elapsed()`.
+    /// `Config::subtract` subtracts the overhead of the bench loop's own measurement instead
+    pub overhead: f64,
+    /// Smallest observed positive difference between two clock readings, in nanoseconds
+    pub resolution: f64,
+}
+
+/// Median samples (of whole batches) within this many multiples of the timer resolution are unreliable
+pub const RESOLUTION_MULTIPLE: f64 = 5.0;
+
+/// Number of the calibration measurements
+pub(crate) const SAMPLES: usize = 1001;
+
+/// Calibration of the clock, measured once on the first call
+pub fn calibration() -> Calibration {
+    static CALIBRATION: OnceLock<Calibration> = OnceLock::new();
+    *CALIBRATION.get_or_init(calibrate)
+}
+
+/// Measures the overhead and the resolution of the clock
+fn calibrate() -> Calibration {
+    let times: Vec<f64> = (0..SAMPLES)
+        .map(|_| {
+            let now = Instant::now();
+            now.elapsed().as_nanos() as f64
+        })
+        .collect();
+    let overhead = times.medf_checked().expect("timer calibration Nan detected");
+    let mut resolution = f64::INFINITY;
+    for _ in 0..SAMPLES {
+        let start = Instant::now();
+        let mut next = Instant::now();
+        while next == start {
+            next = Instant::now();
+        }
+        resolution = resolution.min((next - start).as_nanos() as f64);
+    }
+    Calibration { overhead, resolution }
+}
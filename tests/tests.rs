@@ -6,8 +6,14 @@
 #[cfg(test)]
 use indxvec::{ here, printing::*, Indices, Printing, Vecops, Mutops};
 use ran::*;
-use std::convert::From;
-use times::{bench,mutbenchu8,mutbenchu16,mutbenchu64,mutbenchf64};
+use std::{convert::From, hint::black_box, sync::{atomic::{AtomicU64, Ordering}, Arc}};
+use times::{
+    allocs::{self, CountingAlloc}, harness::{self, Args}, rng::Rng, baseline::{Baseline, Verdict}, stats, timer::{self, Clock}, BenchReport, Config, Measurement, DataGen, Distribution,
+    bench, benchfnmut, benchgen, benchu64, benchscaling, mutbenchgen, mutbenchscaling, mutbenchverify,
+    mutbenchu8, mutbenchu16, mutbenchu64, mutbenchf64, mutbenchi32, mutbenchvvf32 };
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
 
 #[test]
 fn benchtests() {
@@ -53,3 +59,438 @@ fn rantest() {
     // Rnum encapsulates the type of the data items
     bench(8,&NAMES,&CLOSURES);
 }
+
+#[test]
+fn reporttest() {
+    const NAMES:[&str;2] = [ "rust-sort","mutisort" ];
+    const CLOSURES:[fn(&mut[u64]);2] = [
+        |v:&mut[_]| { v.sort_unstable(); },
+        |v:&mut[_]| { v.mutisort(0..v.len(),|a,b| a.cmp(b)); } ];
+
+    set_seeds(0);
+    let report = mutbenchu64(100..1000,300,5,&NAMES,&CLOSURES);
+    assert_eq!(report.results.len(),3);
+    for length in &report.results {
+        assert_eq!(length.algos.len(),2);
+        for algo in &length.algos {
+            assert_eq!(algo.samples,5);
+            assert_eq!(algo.times.len(),5);
+            assert!(algo.median > 0.0);
+        }
+    }
+    let path = std::env::temp_dir().join("times_reporttest.json");
+    report.write_json(&path).expect("write_json failed");
+    let json = std::fs::read_to_string(&path).unwrap();
+    assert!(json.starts_with("{\"data\":\"&mut[u64]\",\"lengths\":[100,1000],\"step\":300"));
+    assert!(json.contains("\"name\":\"mutisort\""));
+    let csv = report.to_csv();
+    assert_eq!(csv.lines().count(),1+3*2);
+    assert!(csv.lines().nth(1).unwrap().starts_with("&mut[u64],100,rust-sort,"));
+    let path = std::env::temp_dir().join("times_reporttest.csv");
+    report.save_baseline(&path).expect("save_baseline failed");
+    let mut baseline = Baseline::load(&path).expect("Baseline::load failed");
+    assert_eq!(baseline,Baseline::from(&report));
+    assert!(report.compare(&baseline).iter().all(|c| c.verdict == Verdict::Unchanged));
+    for entry in &mut baseline.entries { entry.median *= 10.0; }
+    assert!(report.compare(&baseline).iter().all(|c| c.verdict == Verdict::Improved));
+}
+
+#[test]
+fn gentest() {
+    const NAMES:[&str;2] = [ "sums","maxes" ];
+    const CLOSURES:[fn(&[Vec<f64>]);2] = [
+        |v:&[_]| { v.iter().map(|row| row.iter().sum::<f64>()).sum::<f64>(); },
+        |v:&[_]| { v.iter().map(|row| row.iter().fold(0_f64,|m,&x| m.max(x))).sum::<f64>(); } ];
+
+    set_seeds(0);
+    let report = benchgen(&Config::default(),10,100..1000,400,5,&NAMES,&mut CLOSURES.to_vec());
+    assert_eq!(report.data,"&[Vec<f64>]");
+    assert_eq!(report.rows,10);
+    assert_eq!(report.results.len(),3);
+}
+
+#[test]
+fn closurestest() {
+    const NAMES:[&str;2] = [ "k-th by sort","counted sum" ];
+    let k = 3; // captured parameter
+    let mut calls = 0_usize; // captured mutable state
+    let mut kth = |v:&[u64]| { let mut s = v.to_vec(); s.sort_unstable(); assert!(s[k] >= s[0]); };
+    let mut sum = |v:&[u64]| { calls += 1; v.iter().fold(0_u64,|s,&x| s.wrapping_add(x)); };
+    let mut closures = [ &mut kth as &mut dyn FnMut(&[u64]), &mut sum ];
+
+    set_seeds(0);
+    benchgen(&Config::default(),1,10..1000,200,5,&NAMES,&mut closures);
+    assert_eq!(calls,5*5);
+}
+
+#[test]
+fn statstest() {
+    let a = [1.,2.,3.,4.,5.];
+    let b = [6.,7.,8.,9.,10.];
+    assert!((stats::mannwhitney(&a,&b) - 0.0122).abs() < 1e-3);
+    assert!(stats::mannwhitney(&a,&a) > 0.99);
+    assert!((stats::normcdf(1.96) - 0.975).abs() < 1e-4);
+}
+
+#[test]
+fn complexitytest() {
+    let lengths = [1000.,2000.,4000.,8000.];
+    let times:Vec<f64> = lengths.iter().map(|n| 3.0*n*n).collect();
+    assert!((stats::fitmodel(stats::Model::Quadratic,&lengths,&times).0-3.0).abs() < 1e-9);
+    assert!((stats::loglogslope(&lengths,&times)-2.0).abs() < 1e-9);
+
+    const NAMES:[&str;2] = [ "rust-sort","sum" ];
+    // the sum is returned, so that it is not optimised away
+    const CLOSURES:[fn(&mut[u64]) -> u64;2] = [
+        |v:&mut[_]| { v.sort_unstable(); v[0] },
+        |v:&mut[_]| { v.iter().fold(0_u64,|s,&x| s.wrapping_add(x)) } ];
+    set_seeds(0);
+    let fits = mutbenchu64(2000..20000,4000,10,&NAMES,&CLOSURES).complexity();
+    assert_eq!(fits.len(),2);
+    assert!(fits.iter().all(|f| f.slope > 0.5));
+}
+
+#[test]
+fn autotest() {
+    const NAMES:[&str;2] = [ "rust-sort","mutisort" ];
+    let cfg = Config { warmup: 3, target: Some(2.0), maxrepeats: 200, ..Config::default() };
+    set_seeds(0);
+    let report = mutbenchgen(&cfg,1,1000..3000,1000,5,&NAMES,&mut [
+        |v:&mut[u64]| { v.sort_unstable(); },
+        |v:&mut[u64]| { v.mutisort(0..v.len(),|a,b| a.cmp(b)); } ]);
+    for algo in report.results.iter().flat_map(|r| &r.algos) {
+        assert!(algo.samples >= 5 && algo.samples <= 200);
+        assert!(algo.samples == 200 || algo.madpc() <= 2.0);
+    }
+}
+
+#[test]
+fn disttest() {
+    let mut rng = Rng::new(0);
+    let v = u64::generate(1,100,&Distribution::Sorted,&mut rng);
+    assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    let v = f64::generate(1,100,&Distribution::Reversed,&mut rng);
+    assert!(v.windows(2).all(|w| w[0] >= w[1]));
+    let v = u16::generate(1,100,&Distribution::OrganPipe,&mut rng);
+    assert!(v[..50].windows(2).all(|w| w[0] <= w[1]) && v[50..].windows(2).all(|w| w[0] >= w[1]));
+    let v = u8::generate(1,100,&Distribution::AllEqual,&mut rng);
+    assert!(v.iter().all(|&x| x == v[0]));
+    let v = u64::generate(1,1000,&Distribution::Zipf { n:10, s:1.0 },&mut rng);
+    assert!(v.iter().all(|&x| (1..=10).contains(&x)));
+    let v = Vec::<u8>::generate(3,100,&Distribution::FewDistinct(2),&mut rng);
+    assert_eq!(v.len(),3);
+    assert!(u64::generate(1,0,&Distribution::Sawtooth(4),&mut rng).is_empty());
+
+    const NAMES:[&str;2] = [ "rust-sort","mutisort" ];
+    let cfg = Config { dist: Distribution::NearlySorted(10), ..Config::default() };
+    mutbenchgen(&cfg,1,100..1000,400,5,&NAMES,&mut [
+        |v:&mut[f64]| { v.sort_unstable_by(|a,b| a.total_cmp(b)); },
+        |v:&mut[f64]| { v.mutisort(0..v.len(),|a,b| a.total_cmp(b)); } ]);
+}
+
+#[test]
+fn typestest() {
+    const NAMES:[&str;2] = [ "rust-sort","mutisort" ];
+    set_seeds(0);
+    let report = mutbenchi32(100..1000,400,5,&NAMES,&[
+        |v:&mut[_]| { v.sort_unstable(); },
+        |v:&mut[_]| { v.mutisort(0..v.len(),|a,b| a.cmp(b)); } ]);
+    assert_eq!(report.data,"&mut[i32]");
+    let report = mutbenchvvf32(5,100..1000,400,5,&NAMES,&[
+        |v:&mut[_]| { for r in v { r.sort_unstable_by(|a,b| a.total_cmp(b)); } },
+        |v:&mut[_]| { for r in v { let n = r.len(); r.mutisort(0..n,|a,b| a.total_cmp(b)); } } ]);
+    assert_eq!(report.data,"&mut[Vec<f32>]");
+    let mut rng = Rng::new(0);
+    assert!(i8::generate(1,1000,&Distribution::Uniform,&mut rng).iter().any(|&x| x < 0));
+    assert!(f32::generate(1,1000,&Distribution::Uniform,&mut rng).iter().all(|x| (0.0..1.0).contains(x)));
+}
+
+#[test]
+fn verifytest() {
+    const NAMES:[&str;2] = [ "rust-sort","mutisort" ];
+    set_seeds(0);
+    mutbenchverify(&Config::default(),1,100..1000,400,5,&NAMES,&mut [
+        |v:&mut[u16]| { v.sort_unstable(); },
+        |v:&mut[u16]| { v.mutisort(0..v.len(),|a,b| a.cmp(b)); } ],
+        |v| v.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[test]
+#[should_panic(expected = "fast-but-wrong output differs from that of rust-sort at length: 100")]
+fn verifyfailtest() {
+    const NAMES:[&str;2] = [ "rust-sort","fast-but-wrong" ];
+    set_seeds(0);
+    let cfg = Config { verify: true, ..Config::default() };
+    mutbenchgen(&cfg,1,100..1000,400,5,&NAMES,&mut [
+        |v:&mut[u16]| { v.sort_unstable(); },
+        |v:&mut[u16]| { v.reverse(); } ]);
+}
+
+#[test]
+fn fingerprinttest() {
+    assert_eq!(f64::fingerprint(&[1.0,2.0]), f64::fingerprint(&[0.5,1.0].map(|x| 2.0*x)));
+    assert_ne!(f64::fingerprint(&[1.0,2.0]), f64::fingerprint(&[2.0,1.0]));
+    assert_ne!(Vec::<u8>::fingerprint(&[vec![1,2],vec![3]]), Vec::<u8>::fingerprint(&[vec![1],vec![2,3]]));
+}
+
+#[test]
+fn blackboxtest() {
+    const NAMES:[&str;2] = [ "sum","max" ];
+    set_seeds(0);
+    // the returned values are consumed by the harness, so the work can not be elided
+    let report = benchu64(1000..100001,99000,5,&NAMES,&[
+        |v:&[u64]| v.iter().fold(0_u64,|s,&x| s.wrapping_add(x)),
+        |v:&[u64]| v.iter().copied().max().unwrap_or(0) ]);
+    // hundred times more data takes much longer, so the work was done
+    for (short,long) in report.results[0].algos.iter().zip(&report.results[1].algos) {
+        assert!(long.median > 10.0*short.median);
+    }
+}
+
+#[test]
+fn timertest() {
+    let timer = timer::calibration();
+    assert!(timer.overhead >= 0.0 && timer.resolution > 0.0);
+    const NAMES:[&str;1] = [ "nothing" ];
+    let cfg = Config { subtract: true, ..Config::default() };
+    let report = benchfnmut(&cfg,101,&NAMES,&mut [|| ()]);
+    assert!(report.results[0].algos[0].median < 10.0*timer.overhead.max(timer.resolution));
+}
+
+#[test]
+fn batchtest() {
+    const NAMES:[&str;2] = [ "min","max" ];
+    let cfg = Config { batch: Some(std::time::Duration::from_micros(20)), ..Config::default() };
+    set_seeds(0);
+    let report = benchgen(&cfg,1,10..30,10,9,&NAMES,&mut [
+        |v:&[u8]| v.iter().copied().min(),
+        |v:&[u8]| v.iter().copied().max() ]);
+    for algo in report.results.iter().flat_map(|r| &r.algos) {
+        assert!(algo.batch > 1);
+    }
+    // tiny closures on large data: the read only calls share a small pool of inputs,
+    // the mutating ones stop doubling at the memory budget of a batch
+    let cfg = Config { batch: Some(std::time::Duration::from_micros(200)), ..Config::default() };
+    let report = benchgen(&cfg,1,100000..100001,1,3,&["first"],&mut [|v:&[u64]| v[0]]);
+    assert!(report.results[0].algos[0].batch > 1);
+    let report = mutbenchgen(&cfg,1,100000..100001,1,3,&["set"],&mut [|v:&mut[u64]| v[0] = 1]);
+    assert!(report.results[0].algos[0].batch*100000*8 <= 1 << 26);
+}
+
+#[test]
+fn alloctest() {
+    const NAMES:[&str;2] = [ "to_vec","sort_unstable" ];
+    set_seeds(0);
+    let report = benchgen(&Config::default(),1,100..101,1,5,&NAMES,&mut [
+        |v:&[u64]| { v.to_vec()[0] },
+        |v:&[u64]| { let mut w = [0_u64;100]; w.copy_from_slice(v); w.sort_unstable(); w[0] } ]);
+    assert!(allocs::installed());
+    let algos = &report.results[0].algos;
+    assert_eq!(algos[0].allocs, 1.0);
+    assert_eq!(algos[0].bytes, 800.0);
+    assert_eq!(algos[0].peak, 800.0);
+    assert_eq!(algos[1].allocs, 0.0);
+    assert_eq!(algos[1].bytes, 0.0);
+    assert_eq!(algos[1].peak, 0.0);
+    if cfg!(target_os = "linux") { assert!(allocs::maxrss().is_some_and(|rss| rss > 0)); };
+}
+
+#[test]
+fn cputest() {
+    const NAMES:[&str;2] = [ "sleep","sum" ];
+    let cfg = Config { secondary: Some(Arc::new(Clock::ThreadCpu)), ..Config::default() };
+    let report = benchfnmut(&cfg,5,&NAMES,&mut [
+        || { std::thread::sleep(std::time::Duration::from_millis(2)); 0 },
+        || { (0..100000_u64).map(black_box).sum() } ]);
+    let sleep = &report.results[0].algos[0];
+    assert!(sleep.median >= 2e6);
+    assert!(sleep.median > timer::DESCHEDULED*sleep.second);
+    let cfg = Config {
+        measurement: Arc::new(Clock::ProcessCpu), secondary: Some(Arc::new(Clock::Wall)), ..Config::default() };
+    let report = benchfnmut(&cfg,5,&NAMES[1..],&mut [ || (0..100000_u64).map(black_box).sum::<u64>() ]);
+    assert!(report.results[0].algos[0].median > 0.0);
+    assert!(report.results[0].algos[0].second > 0.0);
+}
+
+/// Application counter of the calls of `hash`
+static HASHES: AtomicU64 = AtomicU64::new(0);
+
+struct Hashes;
+
+impl Measurement for Hashes {
+    fn name(&self) -> String { "hashes".to_string() }
+    fn unit(&self) -> String { " hashes".to_string() }
+    fn start(&self) -> f64 { HASHES.load(Ordering::Relaxed) as f64 }
+}
+
+fn hash(x: u64) -> u64 {
+    HASHES.fetch_add(1, Ordering::Relaxed);
+    x.wrapping_mul(0x9E3779B97F4A7C15)
+}
+
+#[test]
+fn measurementtest() {
+    const NAMES:[&str;2] = [ "hash all","hash first" ];
+    let cfg = Config { measurement: Arc::new(Hashes), secondary: Some(Arc::new(Clock::Wall)), ..Config::default() };
+    set_seeds(0);
+    let report = benchgen(&cfg,1,50..51,1,5,&NAMES,&mut [
+        |v:&[u64]| { v.iter().map(|&x| hash(x)).fold(0,u64::wrapping_add) },
+        |v:&[u64]| { hash(v[0]) } ]);
+    let algos = &report.results[0].algos;
+    assert_eq!(algos[0].median, 50.0);
+    assert_eq!(algos[1].median, 1.0);
+    assert!(algos[0].second > 0.0);
+    assert_eq!(report.results[0].fastest().name, "hash first");
+}
+
+/// Sum of `v` by `threads` scoped threads
+fn parsum(v: &[u64], threads: usize) -> u64 {
+    let chunk = v.len().div_ceil(threads).max(1);
+    std::thread::scope(|s| {
+        let parts: Vec<_> = v.chunks(chunk)
+            .map(|c| s.spawn(move || c.iter().fold(0_u64, |a, &x| a.wrapping_add(x))))
+            .collect();
+        parts.into_iter().fold(0_u64, |a, p| a.wrapping_add(p.join().unwrap()))
+    })
+}
+
+#[test]
+fn scalingtest() {
+    assert_eq!(times::threadcounts()[0], 1);
+    let cfg = Config { threads: Some(vec![2,4]), ..Config::default() };
+    let report = benchscaling(&cfg,1,10000..10001,1,5,&["parsum"],&mut [ parsum ]);
+    let algos = &report.results[0].algos;
+    let names: Vec<&str> = algos.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, ["parsum/1","parsum/2","parsum/4"]);
+    assert_eq!(algos[0].speedup, 1.0);
+    assert_eq!(algos[2].threads, 4);
+    assert_eq!(algos[2].speedup, algos[0].median/algos[2].median);
+    assert_eq!(algos[2].efficiency(), algos[2].speedup/4.0);
+    let cfg = Config { threads: Some(vec![3]), verify: true, ..Config::default() };
+    let report = mutbenchscaling(&cfg,1,100..101,1,5,&["chunksort"],&mut [
+        |v:&mut [u64], threads: usize| {
+            let chunk = v.len().div_ceil(threads);
+            std::thread::scope(|s| for c in v.chunks_mut(chunk) { s.spawn(|| c.sort_unstable()); });
+            v.sort_unstable() } ]);
+    assert_eq!(report.results[0].algos.len(), 2);
+}
+
+#[test]
+fn interleavetest() {
+    const NAMES:[&str;3] = [ "rust-sort","mutisort","muthashsort" ];
+    let cfg = Config { interleave: true, warmup: 2, ..Config::default() };
+    let report = mutbenchverify(&cfg,1,100..201,100,7,&NAMES,&mut [
+        |v:&mut[u64]| { v.sort_unstable(); },
+        |v:&mut[u64]| { let n = v.len(); v.mutisort(0..n,|a,b| a.cmp(b)); },
+        |v:&mut[u64]| { v.muthashsort(|t:&u64| *t as f64); } ],
+        |v:&[u64]| v.windows(2).all(|w| w[0] <= w[1]));
+    for algo in report.results.iter().flat_map(|r| &r.algos) {
+        assert_eq!(algo.samples, 7);
+    }
+    // every closure sees the same data in each round
+    let (mut firsts, mut lasts) = (Vec::new(), Vec::new());
+    let mut first = |v:&[u8]| firsts.push(v[0]);
+    let mut last = |v:&[u8]| lasts.push(v[0]);
+    benchgen(&cfg,1,10..11,1,5,&NAMES[..2],&mut [&mut first as &mut dyn FnMut(&[u8]), &mut last]);
+    assert_eq!(firsts, lasts);
+}
+
+#[test]
+fn pairedtest() {
+    // drifting times, with b always half of a
+    let a = [10.,20.,30.,40.,50.];
+    let b = [5.,10.,15.,20.,25.];
+    assert_eq!(stats::pairedspeedup(&a,&b), (2.0,0.0));
+    assert!(stats::pairedspeedup(&[],&b).0.is_nan());
+
+    const NAMES:[&str;2] = [ "rust-sort","sum" ];
+    let cfg = Config { interleave: true, reference: Some("rust-sort".to_string()), ..Config::default() };
+    let report = benchgen(&cfg,1,1000..1001,1,9,&NAMES,&mut [
+        |v:&[u64]| { let mut w = v.to_vec(); w.sort_unstable(); w[0] },
+        |v:&[u64]| { v.iter().fold(0_u64,|a,&x| a.wrapping_add(x)) } ]);
+    let algos = &report.results[0].algos;
+    assert_eq!((algos[0].paired, algos[0].pairedmad), (1.0, 0.0));
+    assert!(algos[1].paired > 1.0);
+}
+
+#[test]
+#[should_panic(expected = "Config::reference: no closure named missing")]
+fn referencetest() {
+    let cfg = Config { reference: Some("missing".to_string()), ..Config::default() };
+    benchfnmut(&cfg,5,&["nothing"],&mut [|| ()]);
+}
+
+#[test]
+fn rngtest() {
+    let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+    assert!((0..100).all(|_| a.u64() == b.u64()));
+    assert!((0..100).all(|_| a.range(3..=5) >= 3 && b.index(7) < 7));
+    // closures drawing from `ran` themselves do not disturb the data
+    const NAMES:[&str;2] = [ "first","last" ];
+    let cfg = Config { seed: 7, ..Config::default() };
+    let (mut firsts, mut lasts) = (Vec::new(), Vec::new());
+    let mut first = |v:&[u16]| { firsts.push(v[0]); ran_u64() };
+    let mut last = |v:&[u16]| { lasts.push(v[0]); ran_u64() };
+    let report = benchgen(&cfg,1,10..30,10,5,&NAMES,&mut [&mut first as &mut dyn FnMut(&[u16]) -> u64, &mut last]);
+    assert_eq!(firsts, lasts);
+    assert_eq!(report.seed, 7);
+    // the same seed reproduces the same data
+    let mut again = Vec::new();
+    let rerun = benchgen(&cfg,1,10..30,10,5,&NAMES[..1],&mut [|v:&[u16]| again.push(v[0])]);
+    assert_eq!(firsts, again);
+    assert_eq!(rerun.results[1].seed, report.results[1].seed);
+}
+
+#[test]
+fn locktest() {
+    // benches started together in two threads run one after the other
+    let cfg = Config { warnwait: true, ..Config::default() };
+    let spans: Vec<(std::time::Instant,std::time::Instant)> = std::thread::scope(|s| {
+        let threads: Vec<_> = (0..2).map(|_| s.spawn(|| {
+            let mut stamps = Vec::new();
+            benchgen(&cfg,1,100..101,1,20,&["stamp"],&mut [|v:&[u64]| {
+                stamps.push(std::time::Instant::now());
+                v.iter().fold(0_u64,|a,&x| a.wrapping_add(x)) }]);
+            (stamps[0], *stamps.last().unwrap())
+        })).collect();
+        threads.into_iter().map(|t| t.join().unwrap()).collect()
+    });
+    assert!(spans[0].1 < spans[1].0 || spans[1].1 < spans[0].0);
+    // a panicking bench does not poison the lock
+    let panicked = std::panic::catch_unwind(|| bench(1,&["panic"],&[|| panic!("bench panic")]));
+    assert!(panicked.is_err());
+    bench(1,&["after panic"],&[|| ()]);
+}
+
+#[test]
+fn emptytest() {
+    // no closures: headings, but no results
+    let report = bench(5,&[],&[]);
+    assert!(report.results[0].algos.is_empty());
+    let report = benchgen(&Config::default(),1,10..20,5,3,&[],&mut [] as &mut [fn(&[u64]) -> u64]);
+    assert_eq!(report.results.len(),2);
+    let report = mutbenchgen(&Config::default(),1,10..11,1,3,&[],&mut [] as &mut [fn(&mut[u64])]);
+    assert!(report.to_json().contains("\"algos\":[\n\n]"));
+    assert_eq!(report.to_csv(),format!("{}\n",BenchReport::CSV_HEADER));
+}
+
+#[test]
+fn harnesstest() {
+    let args = |line: &str| Args::parse(line.split_whitespace().map(String::from));
+    let parsed = args("sort --bench --color always -q --skip f64");
+    assert_eq!(parsed, Args { filters: vec!["sort".to_string()], skip: vec!["f64".to_string()],
+        list: false, exact: false, quiet: true, bench: true });
+    assert!(parsed.selected("sort_u8") && !parsed.selected("sort_f64") && !parsed.selected("hash"));
+    let exact = args("sort_u8 --exact --list");
+    assert!(exact.list && exact.selected("sort_u8") && !exact.selected("sort_u8s"));
+    assert!(args("--bench").selected("anything"));
+    fn group() {}
+    assert_eq!(harness::run(&exact, &[("sort_u8", group), ("sort_u64", group)]), 1);
+    // not a bench run, e.g. under cargo test
+    assert_eq!(harness::run(&args("sort"), &[("sort_u8", group)]), 0);
+    assert_eq!(harness::run(&args("sort --bench"), &[("sort_u8", group)]), 1);
+    // --quiet is terse: the headings are suppressed, the results are not
+    harness::run(&args("sort --bench -q"), &[("sort_u8", group)]);
+    assert!(harness::terse() && !harness::quiet());
+    harness::set_terse(false);
+}